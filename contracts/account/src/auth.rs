@@ -1,15 +1,17 @@
 use crate::auth::secp256r1::verify;
+use crate::auth::zk::{PublicInput, ZkVerifier};
 use crate::error::ContractError;
 use cosmwasm_std::{Binary, Deps, Env};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-mod eth_crypto;
+pub(crate) mod eth_crypto;
 pub mod jwt;
 pub mod passkey;
 mod secp256r1;
 mod sign_arb;
 pub mod util;
+pub mod zk;
 
 pub mod testing {
     pub use super::sign_arb::wrap_message;
@@ -48,6 +50,14 @@ pub enum AddAuthenticator {
         url: String,
         credential: Binary,
     },
+    ZkProof {
+        id: u8,
+        verifier: ZkVerifier,
+        vkey_hash: Binary,
+        /// Must include `PublicInput::TxHash`, binding each proof to its tx
+        public_inputs_template: Vec<PublicInput>,
+        proof: Binary,
+    },
 }
 
 impl AddAuthenticator {
@@ -59,18 +69,38 @@ impl AddAuthenticator {
             AddAuthenticator::Jwt { id, .. } => *id,
             AddAuthenticator::Secp256R1 { id, .. } => *id,
             AddAuthenticator::Passkey { id, .. } => *id,
+            AddAuthenticator::ZkProof { id, .. } => *id,
         }
     }
 }
 
 #[derive(Serialize, Deserialize, Clone, JsonSchema, PartialEq, Debug)]
 pub enum Authenticator {
-    Secp256K1 { pubkey: Binary },
-    Ed25519 { pubkey: Binary },
-    EthWallet { address: String },
-    Jwt { aud: String, sub: String },
-    Secp256R1 { pubkey: Binary },
-    Passkey { url: String, passkey: Binary },
+    Secp256K1 {
+        pubkey: Binary,
+    },
+    Ed25519 {
+        pubkey: Binary,
+    },
+    EthWallet {
+        address: String,
+    },
+    Jwt {
+        aud: String,
+        sub: String,
+    },
+    Secp256R1 {
+        pubkey: Binary,
+    },
+    Passkey {
+        url: String,
+        passkey: Binary,
+    },
+    ZkProof {
+        verifier: ZkVerifier,
+        vkey_hash: Binary,
+        public_inputs_template: Vec<PublicInput>,
+    },
 }
 
 impl Authenticator {
@@ -144,6 +174,22 @@ impl Authenticator {
 
                 Ok(true)
             }
+            Authenticator::ZkProof {
+                verifier,
+                vkey_hash,
+                public_inputs_template,
+            } => {
+                let tx_bytes_hash = util::sha256(tx_bytes);
+                zk::verify(
+                    deps,
+                    env.clone().contract.address,
+                    verifier,
+                    vkey_hash,
+                    public_inputs_template,
+                    sig_bytes,
+                    tx_bytes_hash,
+                )
+            }
         }
     }
 }
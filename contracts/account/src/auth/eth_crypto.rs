@@ -53,7 +53,7 @@ pub fn verify(
     }
 }
 
-fn hash_message(msg: &[u8]) -> [u8; 32] {
+pub(crate) fn hash_message(msg: &[u8]) -> [u8; 32] {
     const PREFIX: &str = "\x19Ethereum Signed Message:\n";
 
     let mut bytes = vec![];
@@ -32,13 +32,10 @@ mod tests {
     use crate::auth::sign_arb::wrap_message;
     use crate::auth::util;
     use crate::contract::instantiate;
-    use crate::execute::tests::XionCustomQuery;
     use crate::msg::InstantiateMsg;
     use base64::{engine::general_purpose, Engine as _};
-    use cosmwasm_std::testing::{
-        message_info, mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage,
-    };
-    use cosmwasm_std::{Addr, Api, Binary, OwnedDeps};
+    use cosmwasm_std::testing::{message_info, mock_dependencies, mock_env, MockApi};
+    use cosmwasm_std::{Addr, Api, Binary};
 
     #[test]
     fn test_derive_addr() {
@@ -96,12 +93,8 @@ mod tests {
 
     #[test]
     fn test_init_sign_arb() {
-        let mut deps = OwnedDeps {
-            storage: MockStorage::default(),
-            api: MockApi::default().with_prefix("xion"),
-            querier: MockQuerier::<XionCustomQuery>::new(&[]),
-            custom_query_type: core::marker::PhantomData::<XionCustomQuery>,
-        };
+        let mut deps = mock_dependencies();
+        deps.api = MockApi::default().with_prefix("xion");
         let mut env = mock_env();
         // This is the local faucet address to simplify reuse
         env.contract.address = Addr::unchecked(
@@ -0,0 +1,248 @@
+use crate::error::ContractError::InvalidSignature;
+use crate::error::ContractResult;
+use cosmos_sdk_proto::traits::MessageExt;
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{to_json_binary, Addr, Binary, Deps, QueryRequest, WasmQuery};
+use prost::Message;
+
+/// Where proofs for a `ZkProof` authenticator are sent to be verified.
+#[cw_serde]
+pub enum ZkVerifier {
+    /// A chain query, called with a `QueryVerifyProofRequest`
+    Grpc { path: String },
+    /// A contract implementing `ZkVerifierQueryMsg::Verify`
+    Contract { address: String },
+}
+
+/// A single public input of the circuit. Inputs derived from the tx are
+/// filled in at verification time, in the order they appear in the template.
+#[cw_serde]
+pub enum PublicInput {
+    Literal(Binary),
+    /// The sha256 hash of the signed bytes
+    TxHash,
+    /// The address of this account
+    ContractAddress,
+}
+
+/// The query a verifier contract must answer, returning a `bool`.
+#[cw_serde]
+pub enum ZkVerifierQueryMsg {
+    Verify {
+        vkey_hash: Binary,
+        proof: Binary,
+        public_inputs: Vec<Binary>,
+    },
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct QueryVerifyProofRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub vkey_hash: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub proof: Vec<u8>,
+    #[prost(bytes = "vec", repeated, tag = "3")]
+    pub public_inputs: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct QueryVerifyProofResponse {
+    #[prost(bool, tag = "1")]
+    pub verified: bool,
+}
+
+pub fn public_inputs(template: &[PublicInput], addr: &Addr, tx_hash: &[u8]) -> Vec<Binary> {
+    template
+        .iter()
+        .map(|input| match input {
+            PublicInput::Literal(value) => value.clone(),
+            PublicInput::TxHash => Binary::from(tx_hash),
+            PublicInput::ContractAddress => Binary::from(addr.as_bytes()),
+        })
+        .collect()
+}
+
+pub fn verify(
+    deps: Deps,
+    addr: Addr,
+    verifier: &ZkVerifier,
+    vkey_hash: &Binary,
+    public_inputs_template: &[PublicInput],
+    proof: &Binary,
+    tx_hash: Vec<u8>,
+) -> ContractResult<bool> {
+    let public_inputs = public_inputs(public_inputs_template, &addr, &tx_hash);
+
+    let verified = match verifier {
+        ZkVerifier::Grpc { path } => {
+            let query_bz = QueryVerifyProofRequest {
+                vkey_hash: vkey_hash.to_vec(),
+                proof: proof.to_vec(),
+                public_inputs: public_inputs.into_iter().map(|i| i.to_vec()).collect(),
+            }
+            .to_bytes()?;
+            let query_response = deps
+                .querier
+                .query_grpc(path.clone(), Binary::new(query_bz))?;
+            QueryVerifyProofResponse::decode(query_response.as_slice())?.verified
+        }
+        ZkVerifier::Contract { address } => {
+            deps.querier
+                .query::<bool>(&QueryRequest::Wasm(WasmQuery::Smart {
+                    contract_addr: address.clone(),
+                    msg: to_json_binary(&ZkVerifierQueryMsg::Verify {
+                        vkey_hash: vkey_hash.clone(),
+                        proof: proof.clone(),
+                        public_inputs,
+                    })?,
+                }))?
+        }
+    };
+
+    if !verified {
+        return Err(InvalidSignature);
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::auth::util::sha256;
+    use crate::auth::zk::{
+        verify, PublicInput, QueryVerifyProofRequest, QueryVerifyProofResponse, ZkVerifier,
+        ZkVerifierQueryMsg,
+    };
+    use crate::error::ContractError;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, MockApi, MockStorage};
+    use cosmwasm_std::{
+        from_json, to_json_binary, Binary, ContractResult, Empty, GrpcQuery, OwnedDeps, Querier,
+        QuerierResult, QueryRequest, SystemResult, WasmQuery,
+    };
+    use prost::Message;
+    use std::marker::PhantomData;
+
+    const VERIFIER: &str = "verifier";
+    const VERIFY_PATH: &str = "/xion.zk.v1.Query/VerifyProof";
+
+    // a mock chain verifier with the same rule as `mock_verifier`
+    struct MockGrpcVerifier;
+
+    impl Querier for MockGrpcVerifier {
+        fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+            match from_json::<QueryRequest<Empty>>(bin_request).unwrap() {
+                QueryRequest::Grpc(GrpcQuery { path, data }) if path == VERIFY_PATH => {
+                    let request = QueryVerifyProofRequest::decode(data.as_slice()).unwrap();
+                    assert_eq!(request.vkey_hash, b"vkey");
+                    let verified = request.proof == request.public_inputs.concat();
+                    SystemResult::Ok(ContractResult::Ok(Binary::from(
+                        QueryVerifyProofResponse { verified }.encode_to_vec(),
+                    )))
+                }
+                _ => panic!("unexpected query"),
+            }
+        }
+    }
+
+    // a mock verifier contract that accepts a proof equal to the concatenation
+    // of the public inputs
+    fn mock_verifier(query: &WasmQuery) -> cosmwasm_std::QuerierResult {
+        match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == VERIFIER => {
+                let ZkVerifierQueryMsg::Verify {
+                    vkey_hash,
+                    proof,
+                    public_inputs,
+                } = from_json(msg).unwrap();
+                assert_eq!(vkey_hash, Binary::from(b"vkey"));
+                let expected: Vec<u8> = public_inputs.iter().flat_map(|i| i.to_vec()).collect();
+                SystemResult::Ok(ContractResult::Ok(
+                    to_json_binary(&(proof.to_vec() == expected)).unwrap(),
+                ))
+            }
+            _ => panic!("unexpected query"),
+        }
+    }
+
+    #[test]
+    fn test_verify_with_contract_verifier() {
+        let mut deps = mock_dependencies();
+        deps.querier.update_wasm(mock_verifier);
+        let env = mock_env();
+
+        let verifier = ZkVerifier::Contract {
+            address: VERIFIER.to_string(),
+        };
+        let template = vec![
+            PublicInput::Literal(Binary::from(b"domain")),
+            PublicInput::TxHash,
+        ];
+        let tx_hash = sha256(b"tx bytes");
+        let mut proof = b"domain".to_vec();
+        proof.extend_from_slice(&tx_hash);
+
+        assert!(verify(
+            deps.as_ref(),
+            env.contract.address.clone(),
+            &verifier,
+            &Binary::from(b"vkey"),
+            &template,
+            &Binary::from(proof.clone()),
+            tx_hash,
+        )
+        .unwrap());
+
+        // a proof over a different tx must be rejected
+        let result = verify(
+            deps.as_ref(),
+            env.contract.address,
+            &verifier,
+            &Binary::from(b"vkey"),
+            &template,
+            &Binary::from(proof),
+            sha256(b"other tx bytes"),
+        );
+        assert_eq!(result.unwrap_err(), ContractError::InvalidSignature);
+    }
+
+    #[test]
+    fn test_verify_with_grpc_verifier() {
+        let deps: OwnedDeps<_, _, _, Empty> = OwnedDeps {
+            storage: MockStorage::default(),
+            api: MockApi::default(),
+            querier: MockGrpcVerifier,
+            custom_query_type: PhantomData,
+        };
+        let env = mock_env();
+
+        let verifier = ZkVerifier::Grpc {
+            path: VERIFY_PATH.to_string(),
+        };
+        let template = vec![PublicInput::ContractAddress, PublicInput::TxHash];
+        let tx_hash = sha256(b"tx bytes");
+        let mut proof = env.contract.address.as_bytes().to_vec();
+        proof.extend_from_slice(&tx_hash);
+
+        assert!(verify(
+            deps.as_ref(),
+            env.contract.address.clone(),
+            &verifier,
+            &Binary::from(b"vkey"),
+            &template,
+            &Binary::from(proof.clone()),
+            tx_hash,
+        )
+        .unwrap());
+
+        let result = verify(
+            deps.as_ref(),
+            env.contract.address,
+            &verifier,
+            &Binary::from(b"vkey"),
+            &template,
+            &Binary::from(proof),
+            sha256(b"other tx bytes"),
+        );
+        assert_eq!(result.unwrap_err(), ContractError::InvalidSignature);
+    }
+}
@@ -0,0 +1,296 @@
+//! Builds the credentials an account accepts, for wallets and other clients.
+//!
+//! A [`Signer`] describes an authenticator by its public part and a callback
+//! that signs what the account verifies. The callbacks receive exactly the
+//! bytes the key must sign, already hashed where the account expects a
+//! digest, so clients never reimplement the account's message layout.
+
+use crate::auth::eth_crypto::hash_message;
+use crate::auth::util::sha256;
+use crate::auth::AddAuthenticator;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine as _;
+use cosmwasm_std::{Addr, Binary};
+
+pub enum Signer<'a> {
+    /// `sign` produces a compact ECDSA signature of the sha256 digest it is
+    /// given
+    Secp256K1 {
+        pubkey: Binary,
+        sign: &'a dyn Fn(&[u8; 32]) -> [u8; 64],
+    },
+    /// `sign` produces an Ed25519 signature of the message it is given
+    Ed25519 {
+        pubkey: Binary,
+        sign: &'a dyn Fn(&[u8]) -> [u8; 64],
+    },
+    /// `sign` produces a recoverable signature, `r || s || v`, of the
+    /// EIP-191 personal message digest it is given
+    EthWallet {
+        address: String,
+        sign: &'a dyn Fn(&[u8; 32]) -> [u8; 65],
+    },
+    /// `sign` produces a P-256 ECDSA/SHA-256 signature of the message it is
+    /// given
+    Secp256R1 {
+        pubkey: Binary,
+        sign: &'a dyn Fn(&[u8]) -> [u8; 64],
+    },
+    /// `token` issues a JWT for `aud` and `sub` whose `transaction_hash`
+    /// claim carries the bytes it is given
+    Jwt {
+        aud: String,
+        sub: String,
+        token: &'a dyn Fn(&[u8]) -> String,
+    },
+    /// `sign` returns the WebAuthN response for the challenge it is given:
+    /// an attestation when registering and an assertion when signing a tx
+    Passkey {
+        url: String,
+        sign: &'a dyn Fn(&str) -> Vec<u8>,
+    },
+}
+
+impl Signer<'_> {
+    /// The signature `Authenticator::verify` accepts for `message`. Key
+    /// signatures are registered the same way with the account address as
+    /// the message, while JWT and passkey registration embed the address
+    /// differently, see [`Signer::add_authenticator`].
+    fn signature(&self, message: &[u8]) -> Vec<u8> {
+        match self {
+            Signer::Secp256K1 { sign, .. } => sign(&digest(message)).to_vec(),
+            Signer::Ed25519 { sign, .. } => sign(&sha256(message)).to_vec(),
+            Signer::EthWallet { sign, .. } => sign(&hash_message(message)).to_vec(),
+            Signer::Secp256R1 { sign, .. } => sign(&sha256(message)).to_vec(),
+            Signer::Jwt { token, .. } => token(&sha256(message)).into_bytes(),
+            Signer::Passkey { sign, .. } => {
+                sign(&URL_SAFE_NO_PAD.encode(STANDARD.encode(sha256(message))))
+            }
+        }
+    }
+
+    /// The `cred_bytes` for `before_tx`: the authenticator id followed by
+    /// its signature of `tx_bytes`.
+    pub fn cred_bytes(&self, id: u8, tx_bytes: &[u8]) -> Binary {
+        let mut cred_bytes = vec![id];
+        cred_bytes.extend(self.signature(tx_bytes));
+        Binary::new(cred_bytes)
+    }
+
+    /// The payload registering this signer as authenticator `id` of
+    /// `account`, at instantiation or through `AddAuthMethod`.
+    pub fn add_authenticator(&self, id: u8, account: &Addr) -> AddAuthenticator {
+        let message = account.as_bytes();
+        match self {
+            Signer::Secp256K1 { pubkey, .. } => AddAuthenticator::Secp256K1 {
+                id,
+                pubkey: pubkey.clone(),
+                signature: self.signature(message).into(),
+            },
+            Signer::Ed25519 { pubkey, .. } => AddAuthenticator::Ed25519 {
+                id,
+                pubkey: pubkey.clone(),
+                signature: self.signature(message).into(),
+            },
+            Signer::EthWallet { address, .. } => AddAuthenticator::EthWallet {
+                id,
+                address: address.clone(),
+                signature: self.signature(message).into(),
+            },
+            Signer::Secp256R1 { pubkey, .. } => AddAuthenticator::Secp256R1 {
+                id,
+                pubkey: pubkey.clone(),
+                signature: self.signature(message).into(),
+            },
+            // registration carries the address itself rather than a digest
+            Signer::Jwt { aud, sub, token } => AddAuthenticator::Jwt {
+                id,
+                aud: aud.clone(),
+                sub: sub.clone(),
+                token: token(message).into_bytes().into(),
+            },
+            Signer::Passkey { url, sign } => AddAuthenticator::Passkey {
+                id,
+                url: url.clone(),
+                credential: sign(&STANDARD.encode(message)).into(),
+            },
+        }
+    }
+}
+
+fn digest(message: &[u8]) -> [u8; 32] {
+    sha256(message)
+        .try_into()
+        .expect("sha256 digests are 32 bytes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::{execute, instantiate, query, sudo, AccountSudoMsg};
+    use crate::msg::InstantiateMsg;
+    use cosmwasm_std::{instantiate2_address, Api};
+    use cw_multi_test::error::AnyResult;
+    use cw_multi_test::{AppResponse, ContractWrapper, Executor};
+    use k256::ecdsa::signature::hazmat::PrehashSigner;
+    use p256::ecdsa::signature::Signer as _;
+    use tiny_keccak::{Hasher, Keccak};
+    use xion_mock::{webauthn_response, XionApp, XionMockQuerier};
+
+    const SALT: &[u8] = b"account";
+    const TX_BYTES: &[u8] = b"tx sign bytes";
+
+    fn store_account(app: &mut XionApp) -> (u64, Addr) {
+        let code_id = app.store_code(Box::new(
+            ContractWrapper::new(execute, instantiate, query).with_sudo(sudo),
+        ));
+        let checksum = app.wrap().query_wasm_code_info(code_id).unwrap().checksum;
+        let creator = app.api().addr_make("creator");
+        let creator = app.api().addr_canonicalize(creator.as_str()).unwrap();
+        let address = instantiate2_address(checksum.as_slice(), &creator, SALT).unwrap();
+        (code_id, app.api().addr_humanize(&address).unwrap())
+    }
+
+    /// The address the account under test is instantiated at.
+    fn account_address() -> Addr {
+        store_account(&mut XionMockQuerier::default().into_app()).1
+    }
+
+    /// Instantiates an account with the signer as its first authenticator.
+    fn register(signer: &Signer, querier: XionMockQuerier) -> (XionApp, Addr) {
+        let mut app = querier.into_app();
+        let (code_id, account) = store_account(&mut app);
+        let msg = InstantiateMsg {
+            authenticator: signer.add_authenticator(0, &account),
+        };
+        let creator = app.api().addr_make("creator");
+        app.instantiate2_contract(code_id, creator, &msg, &[], "account", None, SALT)
+            .unwrap();
+        (app, account)
+    }
+
+    fn before_tx(app: &mut XionApp, account: &Addr, cred_bytes: Binary) -> AnyResult<AppResponse> {
+        app.wasm_sudo(
+            account.clone(),
+            &AccountSudoMsg::BeforeTx {
+                msgs: vec![],
+                tx_bytes: Binary::from(TX_BYTES),
+                cred_bytes: Some(cred_bytes),
+                simulate: false,
+            },
+        )
+    }
+
+    fn assert_round_trip(signer: Signer) {
+        let (mut app, account) = register(&signer, XionMockQuerier::default());
+        before_tx(&mut app, &account, signer.cred_bytes(0, TX_BYTES)).unwrap();
+        // a credential for another tx is rejected
+        before_tx(&mut app, &account, signer.cred_bytes(0, b"another tx")).unwrap_err();
+    }
+
+    #[test]
+    fn test_secp256k1() {
+        let key = k256::ecdsa::SigningKey::from_slice(&[1; 32]).unwrap();
+        let sign = |digest: &[u8; 32]| -> [u8; 64] {
+            let signature: k256::ecdsa::Signature = key.sign_prehash(digest).unwrap();
+            signature.to_bytes().into()
+        };
+        assert_round_trip(Signer::Secp256K1 {
+            pubkey: key.verifying_key().to_sec1_bytes().to_vec().into(),
+            sign: &sign,
+        });
+    }
+
+    #[test]
+    fn test_ed25519() {
+        let key = ed25519_zebra::SigningKey::from([2; 32]);
+        let pubkey: [u8; 32] = ed25519_zebra::VerificationKey::from(&key).into();
+        let sign = |message: &[u8]| -> [u8; 64] { key.sign(message).into() };
+        assert_round_trip(Signer::Ed25519 {
+            pubkey: pubkey.to_vec().into(),
+            sign: &sign,
+        });
+    }
+
+    #[test]
+    fn test_eth_wallet() {
+        let key = k256::ecdsa::SigningKey::from_slice(&[3; 32]).unwrap();
+        let pubkey = key.verifying_key().to_encoded_point(false);
+        let mut hash = [0u8; 32];
+        let mut keccak = Keccak::v256();
+        keccak.update(&pubkey.as_bytes()[1..]);
+        keccak.finalize(&mut hash);
+
+        let sign = |digest: &[u8; 32]| -> [u8; 65] {
+            let (signature, recovery_id) = key.sign_prehash_recoverable(digest).unwrap();
+            let mut bytes = [0; 65];
+            bytes[..64].copy_from_slice(&signature.to_bytes());
+            bytes[64] = recovery_id.to_byte();
+            bytes
+        };
+        assert_round_trip(Signer::EthWallet {
+            address: format!("0x{}", hex::encode(&hash[12..])),
+            sign: &sign,
+        });
+    }
+
+    #[test]
+    fn test_secp256r1() {
+        let key = p256::ecdsa::SigningKey::from_slice(&[4; 32]).unwrap();
+        let sign = |message: &[u8]| -> [u8; 64] {
+            let signature: p256::ecdsa::Signature = key.sign(message);
+            signature.to_bytes().into()
+        };
+        assert_round_trip(Signer::Secp256R1 {
+            pubkey: key.verifying_key().to_sec1_bytes().to_vec().into(),
+            sign: &sign,
+        });
+    }
+
+    #[test]
+    fn test_jwt() {
+        let token = |transaction_hash: &[u8]| {
+            let claims = format!(
+                r#"{{"transaction_hash":"{}"}}"#,
+                STANDARD.encode(transaction_hash)
+            );
+            format!("header.{}.signature", URL_SAFE_NO_PAD.encode(claims))
+        };
+        let signer = Signer::Jwt {
+            aud: "project".to_string(),
+            sub: "user".to_string(),
+            token: &token,
+        };
+
+        // the chain accepts every token, the account checks what they sign
+        let account = account_address();
+        let querier = XionMockQuerier::default()
+            .with_jwt("project", "user", &token(account.as_bytes()))
+            .with_jwt("project", "user", &token(&sha256(TX_BYTES)))
+            .with_jwt("project", "user", &token(&sha256(b"another tx")));
+        let (mut app, account) = register(&signer, querier);
+        before_tx(&mut app, &account, signer.cred_bytes(0, TX_BYTES)).unwrap();
+
+        before_tx(&mut app, &account, signer.cred_bytes(0, b"another tx")).unwrap_err();
+    }
+
+    #[test]
+    fn test_passkey() {
+        let sign = |challenge: &str| webauthn_response(challenge);
+        let signer = Signer::Passkey {
+            url: "https://xion.example".to_string(),
+            sign: &sign,
+        };
+
+        // the mock registers a fixed credential and accepts responses that
+        // answer the challenge the account derives from what is signed
+        let querier = XionMockQuerier::default().with_passkey(
+            &account_address(),
+            "https://xion.example",
+            Binary::from(b"credential"),
+        );
+        let (mut app, account) = register(&signer, querier);
+        before_tx(&mut app, &account, signer.cred_bytes(0, TX_BYTES)).unwrap();
+        before_tx(&mut app, &account, signer.cred_bytes(0, b"another tx")).unwrap_err();
+    }
+}
@@ -4,8 +4,14 @@ use cosmwasm_std::{
 };
 
 use crate::error::ContractError;
-use crate::execute::{add_auth_method, assert_self, emit, remove_auth_method};
+use crate::execute::{
+    add_auth_method, assert_self, cancel_pending_action, emit, execute_pending_action,
+    remove_auth_method, replace_auth_method, set_authenticator_tier, update_high_risk_msg_types,
+    update_removal_delay,
+};
+use crate::migrations::MIGRATIONS;
 use crate::msg::{ExecuteMsg, MigrateMsg};
+use crate::state::TX_AUTHENTICATOR;
 use crate::{
     error::ContractResult,
     execute,
@@ -61,18 +67,19 @@ pub enum AccountSudoMsg {
 pub fn sudo(deps: DepsMut, env: Env, msg: AccountSudoMsg) -> ContractResult<Response> {
     match msg {
         AccountSudoMsg::BeforeTx {
+            msgs,
             tx_bytes,
             cred_bytes,
             simulate,
-            ..
         } => execute::before_tx(
-            deps.as_ref(),
+            deps,
             &env,
+            &msgs,
             &Binary::from(tx_bytes.as_slice()),
             cred_bytes.as_ref(),
             simulate,
         ),
-        AccountSudoMsg::AfterTx { .. } => execute::after_tx(),
+        AccountSudoMsg::AfterTx { .. } => execute::after_tx(deps),
     }
 }
 
@@ -86,10 +93,26 @@ pub fn execute(
     assert_self(&info.sender, &env.contract.address)?;
     let mut owned_msg = msg.clone();
     match &mut owned_msg {
-        ExecuteMsg::AddAuthMethod { add_authenticator } => {
-            add_auth_method(deps, &env, add_authenticator)
-        }
+        ExecuteMsg::AddAuthMethod {
+            add_authenticator,
+            tier,
+        } => add_auth_method(deps, &env, add_authenticator, tier.clone()),
         ExecuteMsg::RemoveAuthMethod { id } => remove_auth_method(deps, env, *id),
+        ExecuteMsg::ReplaceAuthMethod { add_authenticator } => {
+            replace_auth_method(deps, &env, add_authenticator)
+        }
+        ExecuteMsg::SetAuthenticatorTier { id, tier } => {
+            set_authenticator_tier(deps, env, *id, tier.clone())
+        }
+        ExecuteMsg::UpdateHighRiskMsgTypes { type_urls } => {
+            update_high_risk_msg_types(deps, env, type_urls.clone())
+        }
+        ExecuteMsg::UpdateRemovalDelay { seconds } => update_removal_delay(deps, env, *seconds),
+        ExecuteMsg::ExecutePendingAction { id } => execute_pending_action(deps, env, *id),
+        ExecuteMsg::CancelPendingAction { id } => {
+            let signer = TX_AUTHENTICATOR.may_load(deps.storage)?;
+            cancel_pending_action(deps, env, *id, signer)
+        }
         ExecuteMsg::Emit { data } => emit(env, data.to_string()),
     }
 }
@@ -101,11 +124,17 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::AuthenticatorByID { id } => {
             to_json_binary(&query::authenticator_by_id(deps.storage, id)?)
         }
+        QueryMsg::AuthenticatorTier { id } => {
+            to_json_binary(&query::authenticator_tier(deps.storage, id)?)
+        }
+        QueryMsg::HighRiskMsgTypes {} => to_json_binary(&query::high_risk_msg_types(deps.storage)?),
+        QueryMsg::RemovalDelay {} => to_json_binary(&query::removal_delay(deps.storage)?),
+        QueryMsg::PendingActions {} => to_json_binary(&query::pending_actions(deps.storage)?),
     }
 }
 
 #[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    // No state migrations performed, just returned a Response
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let report = migration::migrate(deps.storage, CONTRACT_NAME, CONTRACT_VERSION, MIGRATIONS)?;
+    Ok(report.into_response())
 }
@@ -9,6 +9,9 @@ pub enum ContractError {
     #[error(transparent)]
     DecodeError(#[from] cosmos_sdk_proto::prost::DecodeError),
 
+    #[error(transparent)]
+    Migration(#[from] migration::MigrationError),
+
     #[error(transparent)]
     Verification(#[from] cosmwasm_std::VerificationError),
 
@@ -81,6 +84,9 @@ pub enum ContractError {
     #[error("emit data too large")]
     EmissionSizeExceeded,
 
+    #[error("zk proof public inputs must include the tx hash")]
+    MissingTxHashInput,
+
     /// Doesn't support PartialEq, moved below
     #[error("{0}")]
     SerdeJSON(String),
@@ -93,6 +99,27 @@ pub enum ContractError {
 
     #[error("authenticator {index} not found")]
     AuthenticatorNotFound { index: u8 },
+
+    #[error("authenticator {index} is not allowed to sign {type_url}")]
+    InsufficientTier { index: u8, type_url: String },
+
+    #[error("cannot remove or downgrade the last admin authenticator")]
+    MinimumAdminCount,
+
+    #[error("pending action {id} not found")]
+    PendingActionNotFound { id: u64 },
+
+    #[error("pending action {id} cannot be executed before {executable_at}")]
+    PendingActionNotReady { id: u64, executable_at: u64 },
+
+    #[error("removal delay exceeds the maximum of {max} seconds")]
+    RemovalDelayTooLong { max: u64 },
+
+    #[error("pending action {id} targets authenticator {index}, which cannot cancel it")]
+    CancelByTarget { id: u64, index: u8 },
+
+    #[error("pending action {id} cannot be cancelled by authenticator {index}")]
+    CancelNotAllowed { id: u64, index: u8 },
 }
 
 pub type ContractResult<T> = Result<T, ContractError>;
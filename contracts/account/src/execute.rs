@@ -1,11 +1,24 @@
 use std::borrow::BorrowMut;
 
-use cosmwasm_std::{Addr, Binary, Deps, DepsMut, Env, Event, Order, Response};
+use cosmos_sdk_proto::cosmos::authz::v1beta1::MsgExec;
+use cosmos_sdk_proto::cosmwasm::wasm::v1::{
+    MsgClearAdmin, MsgExecuteContract, MsgMigrateContract, MsgUpdateAdmin, MsgUpdateContractLabel,
+};
+use cosmos_sdk_proto::prost::Message;
+use cosmwasm_std::{
+    from_json, Addr, AnyMsg, Binary, Deps, DepsMut, Env, Event, Order, Response, Storage, Timestamp,
+};
 
+use crate::auth::zk::{PublicInput, ZkVerifier};
 use crate::auth::{jwt, passkey, AddAuthenticator, Authenticator};
+use crate::msg::ExecuteMsg;
+use crate::state::{
+    AuthAction, AuthenticatorTier, PendingAction, AUTHENTICATOR_TIERS, HIGH_RISK_MSG_TYPES,
+    MAX_REMOVAL_DELAY, NEXT_PENDING_ACTION_ID, PENDING_ACTIONS, REMOVAL_DELAY, TX_AUTHENTICATOR,
+};
 use crate::{
     error::{ContractError, ContractResult},
-    state::AUTHENTICATORS,
+    state::{AUTHENTICATORS, AUTHENTICATORS_ADDED_AT},
 };
 
 pub fn init(
@@ -13,7 +26,7 @@ pub fn init(
     env: Env,
     add_authenticator: &mut AddAuthenticator,
 ) -> ContractResult<Response> {
-    add_auth_method(deps, &env, add_authenticator)?;
+    add_auth_method(deps, &env, add_authenticator, None)?;
 
     Ok(
         Response::new().add_event(Event::new("create_abstract_account").add_attributes(vec![
@@ -25,8 +38,9 @@ pub fn init(
 }
 
 pub fn before_tx(
-    deps: Deps,
+    deps: DepsMut,
     env: &Env,
+    msgs: &[AnyMsg],
     tx_bytes: &Binary,
     cred_bytes: Option<&Binary>,
     simulate: bool,
@@ -70,18 +84,108 @@ pub fn before_tx(
             Authenticator::Passkey { .. } => {
                 // todo: figure out if there are minimum checks for passkeys
             }
+            Authenticator::ZkProof { .. } => {
+                // proof sizes depend on the proving system, the verifier is
+                // responsible for rejecting malformed proofs
+            }
         }
 
-        return match authenticator.verify(deps, env, tx_bytes, sig_bytes)? {
-            true => Ok(Response::new().add_attribute("method", "before_tx")),
-            false => Err(ContractError::InvalidSignature),
-        };
+        if !authenticator.verify(deps.as_ref(), env, tx_bytes, sig_bytes)? {
+            return Err(ContractError::InvalidSignature);
+        }
+
+        assert_tier(deps.as_ref(), env, cred_index, msgs)?;
+        TX_AUTHENTICATOR.save(deps.storage, &cred_index)?;
+    } else if let Some(cred_index) = cred_bytes.and_then(|cred_bytes| cred_bytes.first()) {
+        // simulations are not verified, but should see the same signer
+        TX_AUTHENTICATOR.save(deps.storage, cred_index)?;
     }
 
     Ok(Response::new().add_attribute("method", "before_tx"))
 }
 
-pub fn after_tx() -> ContractResult<Response> {
+/// Ensures an authenticator below the admin tier does not sign account
+/// management or high-risk messages.
+fn assert_tier(deps: Deps, env: &Env, index: u8, msgs: &[AnyMsg]) -> ContractResult<()> {
+    let tier = AUTHENTICATOR_TIERS
+        .may_load(deps.storage, index)?
+        .unwrap_or(AuthenticatorTier::Admin);
+    if tier == AuthenticatorTier::Admin {
+        return Ok(());
+    }
+
+    let high_risk = HIGH_RISK_MSG_TYPES
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    for msg in msgs {
+        assert_msg_tier(env, index, &high_risk, &msg.type_url, msg.value.as_slice())?;
+    }
+
+    Ok(())
+}
+
+fn assert_msg_tier(
+    env: &Env,
+    index: u8,
+    high_risk: &[String],
+    type_url: &str,
+    value: &[u8],
+) -> ContractResult<()> {
+    if high_risk.iter().any(|high_risk| high_risk == type_url)
+        || is_account_management(env, type_url, value)?
+    {
+        return Err(ContractError::InsufficientTier {
+            index,
+            type_url: type_url.to_string(),
+        });
+    }
+
+    // the account may exec messages on its own behalf without any grant, so
+    // wrapped messages are held to the same rules as top-level ones
+    if type_url == "/cosmos.authz.v1beta1.MsgExec" {
+        let exec = MsgExec::decode(value)?;
+        for msg in exec.msgs {
+            assert_msg_tier(env, index, high_risk, &msg.type_url, &msg.value)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn is_account_management(env: &Env, type_url: &str, value: &[u8]) -> ContractResult<bool> {
+    // wasm admin messages can migrate the account to code of the signer's
+    // choosing, so any that target the account are management messages
+    let admin_target = match type_url {
+        "/cosmwasm.wasm.v1.MsgMigrateContract" => Some(MsgMigrateContract::decode(value)?.contract),
+        "/cosmwasm.wasm.v1.MsgUpdateAdmin" => Some(MsgUpdateAdmin::decode(value)?.contract),
+        "/cosmwasm.wasm.v1.MsgClearAdmin" => Some(MsgClearAdmin::decode(value)?.contract),
+        "/cosmwasm.wasm.v1.MsgUpdateContractLabel" => {
+            Some(MsgUpdateContractLabel::decode(value)?.contract)
+        }
+        _ => None,
+    };
+    if let Some(contract) = admin_target {
+        return Ok(contract == env.contract.address.as_str());
+    }
+    if type_url != "/cosmwasm.wasm.v1.MsgExecuteContract" {
+        return Ok(false);
+    }
+
+    let execute = MsgExecuteContract::decode(value)?;
+    if execute.contract != env.contract.address.as_str() {
+        return Ok(false);
+    }
+
+    // messages that don't parse will fail on execution anyway, but are
+    // treated as management messages to be safe
+    Ok(!matches!(
+        from_json::<ExecuteMsg>(&execute.msg),
+        Ok(ExecuteMsg::Emit { .. }) | Ok(ExecuteMsg::CancelPendingAction { .. })
+    ))
+}
+
+pub fn after_tx(deps: DepsMut) -> ContractResult<Response> {
+    TX_AUTHENTICATOR.remove(deps.storage);
     Ok(Response::new().add_attribute("method", "after_tx"))
 }
 
@@ -89,76 +193,87 @@ pub fn add_auth_method(
     deps: DepsMut,
     env: &Env,
     add_authenticator: &mut AddAuthenticator,
+    tier: Option<AuthenticatorTier>,
 ) -> ContractResult<Response> {
+    let auth = verify_add_authenticator(deps.as_ref(), env, add_authenticator)?;
+    let id = add_authenticator.get_id();
+    save_authenticator(deps.storage, id, &auth, env.block.time)?;
+    if let Some(tier) = tier {
+        AUTHENTICATOR_TIERS.save(deps.storage, id, &tier)?;
+    }
+
+    Ok(
+        Response::new().add_event(Event::new("add_auth_method").add_attributes(vec![
+            ("contract_address", env.contract.address.clone().to_string()),
+            ("authenticator", serde_json::to_string(&add_authenticator)?),
+        ])),
+    )
+}
+
+/// Verifies the registration payload of a new authenticator, returning the
+/// authenticator to be stored.
+pub fn verify_add_authenticator(
+    deps: Deps,
+    env: &Env,
+    add_authenticator: &mut AddAuthenticator,
+) -> ContractResult<Authenticator> {
     match add_authenticator.borrow_mut() {
         AddAuthenticator::Secp256K1 {
-            id,
-            pubkey,
-            signature,
+            pubkey, signature, ..
         } => {
             let auth = Authenticator::Secp256K1 {
                 pubkey: (*pubkey).clone(),
             };
 
             if !auth.verify(
-                deps.as_ref(),
+                deps,
                 env,
                 &Binary::from(env.contract.address.as_bytes()),
                 signature,
             )? {
                 Err(ContractError::InvalidSignature)
             } else {
-                save_authenticator(deps, *id, &auth)?;
-                Ok(())
+                Ok(auth)
             }
         }
         AddAuthenticator::Ed25519 {
-            id,
-            pubkey,
-            signature,
+            pubkey, signature, ..
         } => {
             let auth = Authenticator::Ed25519 {
                 pubkey: (*pubkey).clone(),
             };
 
             if !auth.verify(
-                deps.as_ref(),
+                deps,
                 env,
                 &Binary::from(env.contract.address.as_bytes()),
                 signature,
             )? {
                 Err(ContractError::InvalidSignature)
             } else {
-                save_authenticator(deps, *id, &auth)?;
-                Ok(())
+                Ok(auth)
             }
         }
         AddAuthenticator::EthWallet {
-            id,
-            address,
-            signature,
+            address, signature, ..
         } => {
             let auth = Authenticator::EthWallet {
                 address: (*address).clone(),
             };
 
             if !auth.verify(
-                deps.as_ref(),
+                deps,
                 env,
                 &Binary::from(env.contract.address.as_bytes()),
                 signature,
             )? {
                 Err(ContractError::InvalidSignature)
             } else {
-                save_authenticator(deps, *id, &auth)?;
-                Ok(())
+                Ok(auth)
             }
         }
         AddAuthenticator::Jwt {
-            id,
-            aud,
-            sub,
-            token,
+            aud, sub, token, ..
         } => {
             let auth = Authenticator::Jwt {
                 aud: (*aud).clone(),
@@ -166,45 +281,38 @@ pub fn add_auth_method(
             };
 
             jwt::verify(
-                deps.as_ref(),
+                deps,
                 &Binary::from(env.contract.address.as_bytes()).to_vec(),
                 token,
                 aud,
                 sub,
             )?;
 
-            save_authenticator(deps, *id, &auth)?;
-            Ok(())
+            Ok(auth)
         }
         AddAuthenticator::Secp256R1 {
-            id,
-            pubkey,
-            signature,
+            pubkey, signature, ..
         } => {
             let auth = Authenticator::Secp256R1 {
                 pubkey: (*pubkey).clone(),
             };
 
             if !auth.verify(
-                deps.as_ref(),
+                deps,
                 env,
                 &Binary::from(env.contract.address.as_bytes()),
                 signature,
             )? {
                 Err(ContractError::InvalidSignature)
             } else {
-                save_authenticator(deps, *id, &auth)?;
-
-                Ok(())
+                Ok(auth)
             }
         }
         AddAuthenticator::Passkey {
-            id,
-            url,
-            credential,
+            url, credential, ..
         } => {
             let passkey = passkey::register(
-                deps.as_ref(),
+                deps,
                 env.contract.address.clone(),
                 (*url).clone(),
                 (*credential).clone(),
@@ -214,35 +322,183 @@ pub fn add_auth_method(
                 url: (*url).clone(),
                 passkey: passkey.clone(),
             };
-            save_authenticator(deps, *id, &auth)?;
             // we replace the sent credential with the passkey for indexers and other
             // observers to see
             *(credential) = passkey;
-            Ok(())
+            Ok(auth)
         }
-    }?;
-    Ok(
-        Response::new().add_event(Event::new("add_auth_method").add_attributes(vec![
-            ("contract_address", env.contract.address.clone().to_string()),
-            ("authenticator", serde_json::to_string(&add_authenticator)?),
-        ])),
-    )
+        AddAuthenticator::ZkProof {
+            verifier,
+            vkey_hash,
+            public_inputs_template,
+            proof,
+            ..
+        } => {
+            if let ZkVerifier::Contract { address } = verifier {
+                deps.api.addr_validate(address)?;
+            }
+            // without the tx hash, one proof would authorize any tx
+            if !public_inputs_template.contains(&PublicInput::TxHash) {
+                return Err(ContractError::MissingTxHashInput);
+            }
+
+            let auth = Authenticator::ZkProof {
+                verifier: (*verifier).clone(),
+                vkey_hash: (*vkey_hash).clone(),
+                public_inputs_template: (*public_inputs_template).clone(),
+            };
+
+            if !auth.verify(
+                deps,
+                env,
+                &Binary::from(env.contract.address.as_bytes()),
+                proof,
+            )? {
+                Err(ContractError::InvalidSignature)
+            } else {
+                Ok(auth)
+            }
+        }
+    }
 }
 
 pub fn save_authenticator(
-    deps: DepsMut,
+    storage: &mut dyn Storage,
     id: u8,
     authenticator: &Authenticator,
+    added_at: Timestamp,
 ) -> ContractResult<()> {
-    if AUTHENTICATORS.has(deps.storage, id) {
+    if AUTHENTICATORS.has(storage, id) {
         return Err(ContractError::OverridingIndex { index: id });
     }
 
-    AUTHENTICATORS.save(deps.storage, id, authenticator)?;
+    AUTHENTICATORS.save(storage, id, authenticator)?;
+    AUTHENTICATORS_ADDED_AT.save(storage, id, &added_at)?;
     Ok(())
 }
 
 pub fn remove_auth_method(deps: DepsMut, env: Env, id: u8) -> ContractResult<Response> {
+    assert_removable(deps.as_ref(), id)?;
+
+    match REMOVAL_DELAY.may_load(deps.storage)? {
+        Some(delay) => queue_action(deps, env, AuthAction::Remove { id }, delay),
+        None => apply_action(deps, env, AuthAction::Remove { id }),
+    }
+}
+
+pub fn replace_auth_method(
+    deps: DepsMut,
+    env: &Env,
+    add_authenticator: &mut AddAuthenticator,
+) -> ContractResult<Response> {
+    let id = add_authenticator.get_id();
+    if !AUTHENTICATORS.has(deps.storage, id) {
+        return Err(ContractError::AuthenticatorNotFound { index: id });
+    }
+
+    let authenticator = verify_add_authenticator(deps.as_ref(), env, add_authenticator)?;
+    let action = AuthAction::Replace { id, authenticator };
+
+    match REMOVAL_DELAY.may_load(deps.storage)? {
+        Some(delay) => queue_action(deps, env.clone(), action, delay),
+        None => apply_action(deps, env.clone(), action),
+    }
+}
+
+pub fn update_removal_delay(
+    deps: DepsMut,
+    env: Env,
+    seconds: Option<u64>,
+) -> ContractResult<Response> {
+    if seconds.is_some_and(|seconds| seconds > MAX_REMOVAL_DELAY) {
+        return Err(ContractError::RemovalDelayTooLong {
+            max: MAX_REMOVAL_DELAY,
+        });
+    }
+    let action = AuthAction::UpdateRemovalDelay { seconds };
+
+    // any change to the delay must itself wait out the current one: shortening
+    // it would skip the cancel window, and lengthening it could lock out the
+    // owner before they can cancel
+    match REMOVAL_DELAY.may_load(deps.storage)? {
+        Some(delay) => queue_action(deps, env, action, delay),
+        None => apply_action(deps, env, action),
+    }
+}
+
+pub fn execute_pending_action(deps: DepsMut, env: Env, id: u64) -> ContractResult<Response> {
+    let pending = PENDING_ACTIONS
+        .may_load(deps.storage, id)?
+        .ok_or(ContractError::PendingActionNotFound { id })?;
+
+    if env.block.time < pending.executable_at {
+        return Err(ContractError::PendingActionNotReady {
+            id,
+            executable_at: pending.executable_at.seconds(),
+        });
+    }
+
+    PENDING_ACTIONS.remove(deps.storage, id);
+
+    // the account may have changed while the action was pending
+    match &pending.action {
+        AuthAction::Remove { id } => assert_removable(deps.as_ref(), *id)?,
+        AuthAction::Replace { id, .. } => {
+            if !AUTHENTICATORS.has(deps.storage, *id) {
+                return Err(ContractError::AuthenticatorNotFound { index: *id });
+            }
+        }
+        AuthAction::UpdateRemovalDelay { .. } => {}
+    }
+
+    Ok(apply_action(deps, env, pending.action)?.add_attribute("pending_action_id", id.to_string()))
+}
+
+/// Cancels a pending action on behalf of `signer`, the authenticator that
+/// signed the tx. The authenticator an action removes or replaces cannot
+/// cancel it, otherwise a compromised key could keep itself on the account,
+/// and neither can authenticators added after the action was queued, which
+/// such a key could have added. Changes to the removal delay target no
+/// authenticator, so only admin-tier authenticators may cancel them.
+pub fn cancel_pending_action(
+    deps: DepsMut,
+    env: Env,
+    id: u64,
+    signer: Option<u8>,
+) -> ContractResult<Response> {
+    let pending = PENDING_ACTIONS
+        .may_load(deps.storage, id)?
+        .ok_or(ContractError::PendingActionNotFound { id })?;
+    if let Some(target) = pending.target {
+        if signer.is_none_or(|signer| signer == target) {
+            return Err(ContractError::CancelByTarget { id, index: target });
+        }
+    }
+    let Some(signer) = signer else {
+        return Err(ContractError::Unauthorized);
+    };
+    let added_after = AUTHENTICATORS_ADDED_AT
+        .may_load(deps.storage, signer)?
+        .is_some_and(|added_at| added_at >= pending.queued_at);
+    let below_admin = pending.target.is_none()
+        && AUTHENTICATOR_TIERS
+            .may_load(deps.storage, signer)?
+            .is_some_and(|tier| tier != AuthenticatorTier::Admin);
+    if added_after || below_admin {
+        return Err(ContractError::CancelNotAllowed { id, index: signer });
+    }
+
+    PENDING_ACTIONS.remove(deps.storage, id);
+
+    Ok(
+        Response::new().add_event(Event::new("cancel_pending_action").add_attributes(vec![
+            ("contract_address", env.contract.address.to_string()),
+            ("pending_action_id", id.to_string()),
+        ])),
+    )
+}
+
+fn assert_removable(deps: Deps, id: u8) -> ContractResult<()> {
     // Ensure there is more than one authenticator before removing
     if AUTHENTICATORS
         .keys(deps.storage, None, None, Order::Ascending)
@@ -257,17 +513,153 @@ pub fn remove_auth_method(deps: DepsMut, env: Env, id: u8) -> ContractResult<Res
         return Err(ContractError::AuthenticatorNotFound { index: id });
     }
 
-    // Remove the authenticator
-    AUTHENTICATORS.remove(deps.storage, id);
+    assert_other_admin(deps, id)
+}
+
+/// Ensures an admin-tier authenticator other than `id` remains on the account.
+fn assert_other_admin(deps: Deps, id: u8) -> ContractResult<()> {
+    for key in AUTHENTICATORS.keys(deps.storage, None, None, Order::Ascending) {
+        let key = key?;
+        if key != id
+            && AUTHENTICATOR_TIERS
+                .may_load(deps.storage, key)?
+                .is_none_or(|tier| tier == AuthenticatorTier::Admin)
+        {
+            return Ok(());
+        }
+    }
+
+    Err(ContractError::MinimumAdminCount)
+}
+
+pub fn set_authenticator_tier(
+    deps: DepsMut,
+    env: Env,
+    id: u8,
+    tier: AuthenticatorTier,
+) -> ContractResult<Response> {
+    if !AUTHENTICATORS.has(deps.storage, id) {
+        return Err(ContractError::AuthenticatorNotFound { index: id });
+    }
+    if tier != AuthenticatorTier::Admin {
+        assert_other_admin(deps.as_ref(), id)?;
+    }
+
+    AUTHENTICATOR_TIERS.save(deps.storage, id, &tier)?;
 
     Ok(
-        Response::new().add_event(Event::new("remove_auth_method").add_attributes(vec![
+        Response::new().add_event(Event::new("set_authenticator_tier").add_attributes(vec![
             ("contract_address", env.contract.address.to_string()),
             ("authenticator_id", id.to_string()),
+            ("tier", serde_json::to_string(&tier)?),
         ])),
     )
 }
 
+pub fn update_high_risk_msg_types(
+    deps: DepsMut,
+    env: Env,
+    type_urls: Vec<String>,
+) -> ContractResult<Response> {
+    HIGH_RISK_MSG_TYPES.save(deps.storage, &type_urls)?;
+
+    Ok(Response::new().add_event(
+        Event::new("update_high_risk_msg_types").add_attributes(vec![
+            ("contract_address", env.contract.address.to_string()),
+            ("type_urls", type_urls.join(",")),
+        ]),
+    ))
+}
+
+fn queue_action(
+    deps: DepsMut,
+    env: Env,
+    action: AuthAction,
+    delay: u64,
+) -> ContractResult<Response> {
+    let id = NEXT_PENDING_ACTION_ID
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    NEXT_PENDING_ACTION_ID.save(deps.storage, &(id + 1))?;
+
+    let target = match action {
+        AuthAction::Remove { id } | AuthAction::Replace { id, .. } => Some(id),
+        AuthAction::UpdateRemovalDelay { .. } => None,
+    };
+    // delays stored before the maximum existed are capped, so they can still
+    // be lowered
+    let executable_at = delay
+        .min(MAX_REMOVAL_DELAY)
+        .checked_mul(1_000_000_000)
+        .and_then(|nanos| env.block.time.nanos().checked_add(nanos))
+        .map(Timestamp::from_nanos)
+        .ok_or(ContractError::RemovalDelayTooLong {
+            max: MAX_REMOVAL_DELAY,
+        })?;
+    let pending = PendingAction {
+        action,
+        executable_at,
+        target,
+        queued_at: env.block.time,
+    };
+    PENDING_ACTIONS.save(deps.storage, id, &pending)?;
+
+    Ok(
+        Response::new().add_event(Event::new("queue_pending_action").add_attributes(vec![
+            ("contract_address", env.contract.address.to_string()),
+            ("pending_action_id", id.to_string()),
+            ("action", serde_json::to_string(&pending.action)?),
+            ("executable_at", pending.executable_at.seconds().to_string()),
+        ])),
+    )
+}
+
+fn apply_action(deps: DepsMut, env: Env, action: AuthAction) -> ContractResult<Response> {
+    match action {
+        AuthAction::Remove { id } => {
+            // Remove the authenticator
+            AUTHENTICATORS.remove(deps.storage, id);
+            AUTHENTICATORS_ADDED_AT.remove(deps.storage, id);
+            AUTHENTICATOR_TIERS.remove(deps.storage, id);
+
+            Ok(
+                Response::new().add_event(Event::new("remove_auth_method").add_attributes(vec![
+                    ("contract_address", env.contract.address.to_string()),
+                    ("authenticator_id", id.to_string()),
+                ])),
+            )
+        }
+        AuthAction::Replace { id, authenticator } => {
+            AUTHENTICATORS.save(deps.storage, id, &authenticator)?;
+            AUTHENTICATORS_ADDED_AT.save(deps.storage, id, &env.block.time)?;
+
+            Ok(
+                Response::new().add_event(Event::new("replace_auth_method").add_attributes(vec![
+                    ("contract_address", env.contract.address.to_string()),
+                    ("authenticator_id", id.to_string()),
+                    ("authenticator", serde_json::to_string(&authenticator)?),
+                ])),
+            )
+        }
+        AuthAction::UpdateRemovalDelay { seconds } => {
+            match seconds {
+                None => REMOVAL_DELAY.remove(deps.storage),
+                Some(seconds) => REMOVAL_DELAY.save(deps.storage, &seconds)?,
+            }
+
+            Ok(
+                Response::new().add_event(Event::new("update_removal_delay").add_attributes(vec![
+                    ("contract_address", env.contract.address.to_string()),
+                    (
+                        "removal_delay",
+                        seconds.map_or("none".to_string(), |s| s.to_string()),
+                    ),
+                ])),
+            )
+        }
+    }
+}
+
 const MAX_SIZE: usize = 1024;
 pub fn emit(env: Env, data: String) -> ContractResult<Response> {
     if data.len() > MAX_SIZE {
@@ -291,38 +683,71 @@ pub fn assert_self(sender: &Addr, contract: &Addr) -> ContractResult<()> {
 #[cfg(test)]
 pub mod tests {
     use base64::{engine::general_purpose, Engine as _};
-    use cosmwasm_std::testing::{mock_env, MockApi, MockQuerier, MockStorage};
-    use cosmwasm_std::{Binary, CustomQuery, OwnedDeps};
-    use serde::{Deserialize, Serialize};
-
-    use crate::auth::Authenticator;
-    use crate::execute::before_tx;
-    use crate::state::AUTHENTICATORS;
-    use cosmwasm_std::QueryRequest::Custom;
-
-    use cosmos_sdk_proto::xion::v1::{
-        QueryWebAuthNVerifyAuthenticateRequest, QueryWebAuthNVerifyRegisterRequest,
-        QueryWebAuthNVerifyRegisterResponse,
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, MockApi};
+    use cosmwasm_std::{
+        from_json, to_json_binary, to_json_vec, AnyMsg, Binary, ContractResult, SystemResult,
+        WasmQuery,
+    };
+
+    use crate::auth::util::sha256;
+    use crate::auth::zk::{PublicInput, ZkVerifier, ZkVerifierQueryMsg};
+    use crate::auth::{AddAuthenticator, Authenticator};
+    use crate::error::ContractError;
+    use crate::execute::{
+        add_auth_method, assert_tier, before_tx, cancel_pending_action, execute_pending_action,
+        remove_auth_method, save_authenticator, set_authenticator_tier, update_high_risk_msg_types,
+        update_removal_delay,
+    };
+    use crate::msg::ExecuteMsg;
+    use crate::query::pending_actions;
+    use crate::state::{
+        AuthAction, AuthenticatorTier, AUTHENTICATORS, AUTHENTICATOR_TIERS, MAX_REMOVAL_DELAY,
+        REMOVAL_DELAY, TX_AUTHENTICATOR,
+    };
+    use cosmos_sdk_proto::cosmos::authz::v1beta1::MsgExec;
+    use cosmos_sdk_proto::cosmwasm::wasm::v1::{
+        MsgClearAdmin, MsgExecuteContract, MsgMigrateContract, MsgUpdateAdmin,
+        MsgUpdateContractLabel,
     };
+    use cosmos_sdk_proto::prost::Message;
+    use cosmos_sdk_proto::Any;
+    use cosmwasm_std::Env;
 
-    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
-    #[serde(rename_all = "snake_case")]
-    pub enum XionCustomQuery {
-        Verify(QueryWebAuthNVerifyRegisterRequest),
-        Authenticate(QueryWebAuthNVerifyAuthenticateRequest),
+    fn execute_self(env: &Env, msg: &ExecuteMsg) -> AnyMsg {
+        AnyMsg {
+            type_url: "/cosmwasm.wasm.v1.MsgExecuteContract".to_string(),
+            value: MsgExecuteContract {
+                sender: env.contract.address.to_string(),
+                contract: env.contract.address.to_string(),
+                msg: to_json_vec(msg).unwrap(),
+                funds: vec![],
+            }
+            .encode_to_vec()
+            .into(),
+        }
     }
 
-    impl CustomQuery for XionCustomQuery {}
+    /// Wraps `msg` in an authz exec by the account as its own grantee.
+    fn exec_self(env: &Env, msg: AnyMsg) -> AnyMsg {
+        AnyMsg {
+            type_url: "/cosmos.authz.v1beta1.MsgExec".to_string(),
+            value: MsgExec {
+                grantee: env.contract.address.to_string(),
+                msgs: vec![Any {
+                    type_url: msg.type_url,
+                    value: msg.value.to_vec(),
+                }],
+            }
+            .encode_to_vec()
+            .into(),
+        }
+    }
 
     #[test]
     fn test_before_tx() {
         let auth_id = 0;
-        let mut deps = OwnedDeps {
-            storage: MockStorage::default(),
-            api: MockApi::default().with_prefix("xion"),
-            querier: MockQuerier::<XionCustomQuery>::new(&[]),
-            custom_query_type: std::marker::PhantomData,
-        };
+        let mut deps = mock_dependencies();
+        deps.api = MockApi::default().with_prefix("xion");
         let env = mock_env();
 
         let pubkey = "Ayrlj6q3WWs91p45LVKwI8JyfMYNmWMrcDinLNEdWYE4";
@@ -348,51 +773,355 @@ pub mod tests {
         let sig_bytes = Binary::from(new_vec);
         let tx_bytes = Binary::from(general_purpose::STANDARD.decode("Cp0BCpoBChwvY29zbW9zLmJhbmsudjFiZXRhMS5Nc2dTZW5kEnoKP3hpb24xbTZ2aDIwcHM3NW0ybjZxeHdwandmOGZzM2t4dzc1enN5M3YycnllaGQ5c3BtbnUwcTlyc2g0NnljeRIreGlvbjFlMmZ1d2UzdWhxOHpkOW5ra2s4NzZuYXdyd2R1bGd2NDYwdnpnNxoKCgV1eGlvbhIBMRJTCksKQwodL2Fic3RyYWN0YWNjb3VudC52MS5OaWxQdWJLZXkSIgog3pl1PDD1NqnoBnBk5J0wjYzvUFAkWKGTN2lgHc+PAUcSBAoCCAESBBDgpxIaFHhpb24tbG9jYWwtdGVzdG5ldC0xIAg=").unwrap());
 
-        before_tx(deps.as_ref(), &env, &tx_bytes, Some(&sig_bytes), false).unwrap();
+        before_tx(deps.as_mut(), &env, &[], &tx_bytes, Some(&sig_bytes), false).unwrap();
+        assert_eq!(TX_AUTHENTICATOR.load(deps.as_ref().storage).unwrap(), 0);
+
+        // a daily-tier signer cannot manage the account, even through authz
+        AUTHENTICATOR_TIERS
+            .save(deps.as_mut().storage, auth_id, &AuthenticatorTier::Daily)
+            .unwrap();
+        let emit = execute_self(
+            &env,
+            &ExecuteMsg::Emit {
+                data: "{}".to_string(),
+            },
+        );
+        let remove = execute_self(&env, &ExecuteMsg::RemoveAuthMethod { id: 0 });
+        let msgs = [emit.clone(), exec_self(&env, emit)];
+        before_tx(
+            deps.as_mut(),
+            &env,
+            &msgs,
+            &tx_bytes,
+            Some(&sig_bytes),
+            false,
+        )
+        .unwrap();
+        for msgs in [
+            vec![remove.clone()],
+            vec![exec_self(&env, remove.clone())],
+            vec![exec_self(&env, exec_self(&env, remove))],
+        ] {
+            let err = before_tx(
+                deps.as_mut(),
+                &env,
+                &msgs,
+                &tx_bytes,
+                Some(&sig_bytes),
+                false,
+            )
+            .unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::InsufficientTier {
+                    index: 0,
+                    type_url: "/cosmwasm.wasm.v1.MsgExecuteContract".to_string(),
+                }
+            );
+        }
     }
 
     #[test]
-    pub fn test_custom_querier() {
-        let mut deps = OwnedDeps {
-            storage: MockStorage::default(),
-            api: MockApi::default(),
-            querier: MockQuerier::<XionCustomQuery>::new(&[]),
-            custom_query_type: core::marker::PhantomData::<XionCustomQuery>,
-        };
+    fn test_add_zk_auth_method() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let verifier = deps.api.addr_make("verifier");
 
-        deps.querier = deps.querier.with_custom_handler(|query| match query {
-            XionCustomQuery::Verify(data) => {
-                assert_eq!(data.addr, "mock_address");
-                assert_eq!(data.challenge, "mock_challenge");
-                assert_eq!(data.rp, "mock_rp");
-                assert_eq!(data.data, vec![0u8]);
-
-                cosmwasm_std::SystemResult::Ok(cosmwasm_std::ContractResult::Ok(
-                    serde_json::to_vec(&QueryWebAuthNVerifyRegisterResponse {
-                        credential: Binary::from("true".as_bytes()).into(),
-                    })
-                    .unwrap()
-                    .into(),
+        // a verifier contract accepting a proof equal to the concatenated inputs
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { msg, .. } => {
+                let ZkVerifierQueryMsg::Verify {
+                    proof,
+                    public_inputs,
+                    ..
+                } = from_json(msg).unwrap();
+                let expected: Vec<u8> = public_inputs.iter().flat_map(|i| i.to_vec()).collect();
+                SystemResult::Ok(ContractResult::Ok(
+                    to_json_binary(&(proof.to_vec() == expected)).unwrap(),
                 ))
             }
-            XionCustomQuery::Authenticate(_) => todo!(),
+            _ => panic!("unexpected query"),
         });
+        let add_zk = |id: u8, public_inputs_template: Vec<PublicInput>, proof: Vec<u8>| {
+            AddAuthenticator::ZkProof {
+                id,
+                verifier: ZkVerifier::Contract {
+                    address: verifier.to_string(),
+                },
+                vkey_hash: Binary::from(b"vkey"),
+                public_inputs_template,
+                proof: Binary::from(proof),
+            }
+        };
 
-        let query_msg = XionCustomQuery::Verify(QueryWebAuthNVerifyRegisterRequest {
-            addr: "mock_address".to_string(),
-            challenge: "mock_challenge".to_string(),
-            rp: "mock_rp".to_string(),
-            data: vec![0u8],
-        });
-        let query_response = deps
-            .as_ref()
-            .querier
-            .query::<QueryWebAuthNVerifyRegisterResponse>(&Custom(query_msg));
-        assert!(query_response.is_ok());
+        // registration proves over the account address in place of a tx
+        let tx_hash = sha256(env.contract.address.as_bytes());
+        add_auth_method(
+            deps.as_mut(),
+            &env,
+            &mut add_zk(0, vec![PublicInput::TxHash], tx_hash.clone()),
+            None,
+        )
+        .unwrap();
+        assert!(matches!(
+            AUTHENTICATORS.load(deps.as_ref().storage, 0).unwrap(),
+            Authenticator::ZkProof { .. }
+        ));
+
+        let err = add_auth_method(
+            deps.as_mut(),
+            &env,
+            &mut add_zk(1, vec![PublicInput::TxHash], b"bad proof".to_vec()),
+            None,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidSignature);
+
+        // a proof not bound to the tx could be replayed for any tx
+        let err = add_auth_method(
+            deps.as_mut(),
+            &env,
+            &mut add_zk(
+                1,
+                vec![PublicInput::ContractAddress],
+                env.contract.address.as_bytes().to_vec(),
+            ),
+            None,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::MissingTxHashInput);
+    }
+
+    #[test]
+    fn test_delayed_removal() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+
+        let auth = Authenticator::Secp256K1 {
+            pubkey: Binary::from(b"pubkey"),
+        };
+        for id in 0..2 {
+            AUTHENTICATORS
+                .save(deps.as_mut().storage, id, &auth)
+                .unwrap();
+        }
+        update_removal_delay(deps.as_mut(), env.clone(), Some(60)).unwrap();
+
+        // the removal is queued rather than applied
+        remove_auth_method(deps.as_mut(), env.clone(), 1).unwrap();
+        assert!(AUTHENTICATORS.has(deps.as_ref().storage, 1));
+        let pending = pending_actions(deps.as_ref().storage).unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].1.action, AuthAction::Remove { id: 1 });
+
+        let err = execute_pending_action(deps.as_mut(), env.clone(), 0).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::PendingActionNotReady { id: 0, .. }
+        ));
+
+        // the authenticator being removed cannot cancel its own removal
+        let err = cancel_pending_action(deps.as_mut(), env.clone(), 0, Some(1)).unwrap_err();
+        assert_eq!(err, ContractError::CancelByTarget { id: 0, index: 1 });
+        let err = cancel_pending_action(deps.as_mut(), env.clone(), 0, None).unwrap_err();
+        assert_eq!(err, ContractError::CancelByTarget { id: 0, index: 1 });
+
+        // a cancelled action can no longer be executed
+        cancel_pending_action(deps.as_mut(), env.clone(), 0, Some(0)).unwrap();
+        env.block.time = env.block.time.plus_seconds(60);
+        let err = execute_pending_action(deps.as_mut(), env.clone(), 0).unwrap_err();
+        assert_eq!(err, ContractError::PendingActionNotFound { id: 0 });
+
+        remove_auth_method(deps.as_mut(), env.clone(), 1).unwrap();
+        env.block.time = env.block.time.plus_seconds(60);
+        execute_pending_action(deps.as_mut(), env.clone(), 1).unwrap();
+        assert!(!AUTHENTICATORS.has(deps.as_ref().storage, 1));
 
+        // shortening the delay waits out the current one, and so does lengthening it
+        update_removal_delay(deps.as_mut(), env.clone(), None).unwrap();
+        assert_eq!(REMOVAL_DELAY.load(deps.as_ref().storage).unwrap(), 60);
+        update_removal_delay(deps.as_mut(), env.clone(), Some(MAX_REMOVAL_DELAY)).unwrap();
+        assert_eq!(REMOVAL_DELAY.load(deps.as_ref().storage).unwrap(), 60);
+
+        let err = update_removal_delay(deps.as_mut(), env.clone(), Some(MAX_REMOVAL_DELAY + 1))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::RemovalDelayTooLong {
+                max: MAX_REMOVAL_DELAY
+            }
+        );
+
+        // a delay stored before the maximum existed is capped rather than overflowing
+        REMOVAL_DELAY
+            .save(deps.as_mut().storage, &u64::MAX)
+            .unwrap();
+        update_removal_delay(deps.as_mut(), env.clone(), Some(60)).unwrap();
+        let (id, pending) = pending_actions(deps.as_ref().storage)
+            .unwrap()
+            .pop()
+            .unwrap();
         assert_eq!(
-            query_response.unwrap().credential,
-            Binary::from("true".as_bytes())
+            pending.executable_at,
+            env.block.time.plus_seconds(MAX_REMOVAL_DELAY)
         );
+        env.block.time = pending.executable_at;
+        execute_pending_action(deps.as_mut(), env, id).unwrap();
+        assert_eq!(REMOVAL_DELAY.load(deps.as_ref().storage).unwrap(), 60);
+    }
+
+    #[test]
+    fn test_cancel_by_new_authenticator() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+
+        let auth = Authenticator::Secp256K1 {
+            pubkey: Binary::from(b"pubkey"),
+        };
+        for id in 0..3 {
+            save_authenticator(deps.as_mut().storage, id, &auth, env.block.time).unwrap();
+        }
+        update_removal_delay(deps.as_mut(), env.clone(), Some(60)).unwrap();
+        set_authenticator_tier(deps.as_mut(), env.clone(), 0, AuthenticatorTier::Daily).unwrap();
+        env.block.time = env.block.time.plus_seconds(1);
+
+        // a compromised key adds a sibling to cancel its own removal
+        remove_auth_method(deps.as_mut(), env.clone(), 1).unwrap();
+        save_authenticator(deps.as_mut().storage, 3, &auth, env.block.time).unwrap();
+        let err = cancel_pending_action(deps.as_mut(), env.clone(), 0, Some(3)).unwrap_err();
+        assert_eq!(err, ContractError::CancelNotAllowed { id: 0, index: 3 });
+        env.block.time = env.block.time.plus_seconds(1);
+        save_authenticator(deps.as_mut().storage, 4, &auth, env.block.time).unwrap();
+        let err = cancel_pending_action(deps.as_mut(), env.clone(), 0, Some(4)).unwrap_err();
+        assert_eq!(err, ContractError::CancelNotAllowed { id: 0, index: 4 });
+
+        // delay changes target no authenticator, so they need an admin to cancel
+        update_removal_delay(deps.as_mut(), env.clone(), None).unwrap();
+        env.block.time = env.block.time.plus_seconds(1);
+        let err = cancel_pending_action(deps.as_mut(), env.clone(), 1, Some(0)).unwrap_err();
+        assert_eq!(err, ContractError::CancelNotAllowed { id: 1, index: 0 });
+        let err = cancel_pending_action(deps.as_mut(), env.clone(), 1, Some(4)).unwrap_err();
+        assert_eq!(err, ContractError::CancelNotAllowed { id: 1, index: 4 });
+        cancel_pending_action(deps.as_mut(), env.clone(), 1, Some(2)).unwrap();
+
+        // authenticators from before the action was queued still can
+        cancel_pending_action(deps.as_mut(), env, 0, Some(0)).unwrap();
+        assert!(pending_actions(deps.as_ref().storage).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tier_restrictions() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        let auth = Authenticator::Secp256K1 {
+            pubkey: Binary::from(b"pubkey"),
+        };
+        for id in 0..2 {
+            AUTHENTICATORS
+                .save(deps.as_mut().storage, id, &auth)
+                .unwrap();
+        }
+        set_authenticator_tier(deps.as_mut(), env.clone(), 1, AuthenticatorTier::Daily).unwrap();
+        update_high_risk_msg_types(
+            deps.as_mut(),
+            env.clone(),
+            vec!["/cosmos.bank.v1beta1.MsgSend".to_string()],
+        )
+        .unwrap();
+
+        let remove = execute_self(&env, &ExecuteMsg::RemoveAuthMethod { id: 0 });
+        let emit = execute_self(
+            &env,
+            &ExecuteMsg::Emit {
+                data: "{}".to_string(),
+            },
+        );
+        let send = AnyMsg {
+            type_url: "/cosmos.bank.v1beta1.MsgSend".to_string(),
+            value: Binary::default(),
+        };
+
+        assert_tier(deps.as_ref(), &env, 1, std::slice::from_ref(&emit)).unwrap();
+        for msg in [remove.clone(), send.clone()] {
+            let err = assert_tier(deps.as_ref(), &env, 1, &[emit.clone(), msg]).unwrap_err();
+            assert!(matches!(
+                err,
+                ContractError::InsufficientTier { index: 1, .. }
+            ));
+        }
+        let err =
+            assert_tier(deps.as_ref(), &env, 1, &[exec_self(&env, send.clone())]).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::InsufficientTier { index: 1, .. }
+        ));
+        assert_tier(deps.as_ref(), &env, 0, &[remove, send]).unwrap();
+
+        // wasm admin messages could migrate the account to other code
+        let admin_msgs = |contract: &str| {
+            let contract = contract.to_string();
+            let sender = env.contract.address.to_string();
+            vec![
+                AnyMsg {
+                    type_url: "/cosmwasm.wasm.v1.MsgMigrateContract".to_string(),
+                    value: MsgMigrateContract {
+                        sender: sender.clone(),
+                        contract: contract.clone(),
+                        code_id: 2,
+                        msg: b"{}".to_vec(),
+                    }
+                    .encode_to_vec()
+                    .into(),
+                },
+                AnyMsg {
+                    type_url: "/cosmwasm.wasm.v1.MsgUpdateAdmin".to_string(),
+                    value: MsgUpdateAdmin {
+                        sender: sender.clone(),
+                        new_admin: "attacker".to_string(),
+                        contract: contract.clone(),
+                    }
+                    .encode_to_vec()
+                    .into(),
+                },
+                AnyMsg {
+                    type_url: "/cosmwasm.wasm.v1.MsgClearAdmin".to_string(),
+                    value: MsgClearAdmin {
+                        sender: sender.clone(),
+                        contract: contract.clone(),
+                    }
+                    .encode_to_vec()
+                    .into(),
+                },
+                AnyMsg {
+                    type_url: "/cosmwasm.wasm.v1.MsgUpdateContractLabel".to_string(),
+                    value: MsgUpdateContractLabel {
+                        sender,
+                        new_label: "label".to_string(),
+                        contract,
+                    }
+                    .encode_to_vec()
+                    .into(),
+                },
+            ]
+        };
+        for msg in admin_msgs(env.contract.address.as_str()) {
+            for msg in [msg.clone(), exec_self(&env, msg)] {
+                let err = assert_tier(deps.as_ref(), &env, 1, &[msg]).unwrap_err();
+                assert!(matches!(
+                    err,
+                    ContractError::InsufficientTier { index: 1, .. }
+                ));
+            }
+        }
+        // other contracts the account administers are not the account
+        assert_tier(deps.as_ref(), &env, 1, &admin_msgs("other")).unwrap();
+
+        // the only admin cannot be downgraded or removed
+        let err = set_authenticator_tier(deps.as_mut(), env.clone(), 0, AuthenticatorTier::Daily)
+            .unwrap_err();
+        assert_eq!(err, ContractError::MinimumAdminCount);
+        let err = remove_auth_method(deps.as_mut(), env, 0).unwrap_err();
+        assert_eq!(err, ContractError::MinimumAdminCount);
     }
 }
@@ -1,9 +1,12 @@
 extern crate core;
 
 mod auth;
+#[cfg(any(test, feature = "client"))]
+pub mod client;
 pub mod contract;
 pub mod error;
 pub mod execute;
+mod migrations;
 pub mod msg;
 pub mod query;
 pub mod state;
@@ -28,4 +31,5 @@ pub mod testing {
     pub use super::auth::util;
 }
 
+pub use auth::zk::{PublicInput, ZkVerifier, ZkVerifierQueryMsg};
 pub use auth::AddAuthenticator;
@@ -0,0 +1,100 @@
+use migration::Migration;
+
+/// State transforms by the version that introduced them, see
+/// [`migration::migrate`]. Authenticator tiers, removal delays and pending
+/// actions added since 0.1.1 all default on read, so there is nothing to
+/// transform yet.
+pub const MIGRATIONS: &[Migration] = &[];
+
+#[cfg(test)]
+mod tests {
+    use crate::auth::Authenticator;
+    use crate::contract::{migrate, query};
+    use crate::error::ContractError;
+    use crate::msg::{MigrateMsg, QueryMsg};
+    use crate::state::AuthenticatorTier;
+    use crate::{CONTRACT_NAME, CONTRACT_VERSION};
+    use cosmwasm_std::testing::{mock_dependencies, mock_env};
+    use cosmwasm_std::{from_json, to_json_vec, Binary, Storage};
+    use cw_storage_plus::Map;
+    use migration::MigrationError;
+    use serde_json::Value;
+
+    /// Writes state as stored by an earlier version, in its raw JSON form.
+    fn load_fixture(storage: &mut dyn Storage, fixture: &str) {
+        let fixture: Value = serde_json::from_str(fixture).unwrap();
+        storage.set(
+            b"contract_info",
+            &to_json_vec(&fixture["contract_info"]).unwrap(),
+        );
+        let authenticators: Map<u8, Value> = Map::new("authenticators");
+        for (id, authenticator) in fixture["authenticators"].as_object().unwrap() {
+            authenticators
+                .save(storage, id.parse().unwrap(), authenticator)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_migrate_v0_1_1() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        load_fixture(
+            deps.as_mut().storage,
+            include_str!("../tests/fixtures/v0_1_1.json"),
+        );
+
+        migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+
+        let version = cw2::get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.contract, CONTRACT_NAME);
+        assert_eq!(version.version, CONTRACT_VERSION);
+
+        let ids: Vec<u8> =
+            from_json(query(deps.as_ref(), env.clone(), QueryMsg::AuthenticatorIDs {}).unwrap())
+                .unwrap();
+        assert_eq!(ids, vec![0, 1]);
+
+        let encoded: String = from_json(
+            query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::AuthenticatorByID { id: 1 },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let authenticator: Authenticator =
+            from_json(Binary::from_base64(&encoded).unwrap()).unwrap();
+        assert_eq!(
+            authenticator,
+            Authenticator::Jwt {
+                aud: "project-test".to_string(),
+                sub: "user-1".to_string(),
+            }
+        );
+
+        // authenticators from before tiers existed keep full control
+        let tier: AuthenticatorTier = from_json(
+            query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::AuthenticatorTier { id: 0 },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(tier, AuthenticatorTier::Admin);
+    }
+
+    #[test]
+    fn test_migrate_rejects_downgrade() {
+        let mut deps = mock_dependencies();
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::Migration(MigrationError::Downgrade { .. })
+        ));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::auth::AddAuthenticator;
+use crate::state::{AuthenticatorTier, PendingAction};
 use cosmwasm_schema::{cw_serde, QueryResponses};
 use cosmwasm_std::Binary;
 
@@ -9,9 +10,41 @@ pub struct InstantiateMsg {
 
 #[cw_serde]
 pub enum ExecuteMsg {
-    AddAuthMethod { add_authenticator: AddAuthenticator },
-    RemoveAuthMethod { id: u8 },
-    Emit { data: String },
+    AddAuthMethod {
+        add_authenticator: AddAuthenticator,
+        /// Defaults to `AuthenticatorTier::Admin`
+        tier: Option<AuthenticatorTier>,
+    },
+    RemoveAuthMethod {
+        id: u8,
+    },
+    SetAuthenticatorTier {
+        id: u8,
+        tier: AuthenticatorTier,
+    },
+    /// Set the message type urls only admin-tier authenticators may sign
+    UpdateHighRiskMsgTypes {
+        type_urls: Vec<String>,
+    },
+    /// Replace the authenticator at the id of `add_authenticator`
+    ReplaceAuthMethod {
+        add_authenticator: AddAuthenticator,
+    },
+    /// Set the delay, in seconds, applied to removals and replacements of
+    /// authenticators, up to 30 days. `None` applies them immediately. While
+    /// a delay is set, changing it waits out the current delay too.
+    UpdateRemovalDelay {
+        seconds: Option<u64>,
+    },
+    ExecutePendingAction {
+        id: u64,
+    },
+    CancelPendingAction {
+        id: u64,
+    },
+    Emit {
+        data: String,
+    },
 }
 
 #[cw_serde]
@@ -23,6 +56,19 @@ pub enum QueryMsg {
 
     #[returns(Binary)]
     AuthenticatorByID { id: u8 },
+
+    #[returns(AuthenticatorTier)]
+    AuthenticatorTier { id: u8 },
+
+    #[returns(Vec<String>)]
+    HighRiskMsgTypes {},
+
+    #[returns(Option<u64>)]
+    RemovalDelay {},
+
+    /// Query the removals and replacements waiting for their delay to pass.
+    #[returns(Vec<(u64, PendingAction)>)]
+    PendingActions {},
 }
 
 #[cw_serde]
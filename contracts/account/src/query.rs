@@ -1,6 +1,9 @@
 use cosmwasm_std::{Order, StdResult, Storage};
 
-use crate::state::AUTHENTICATORS;
+use crate::state::{
+    AuthenticatorTier, PendingAction, AUTHENTICATORS, AUTHENTICATOR_TIERS, HIGH_RISK_MSG_TYPES,
+    PENDING_ACTIONS, REMOVAL_DELAY,
+};
 
 pub fn authenticator_ids(store: &dyn Storage) -> StdResult<Vec<u8>> {
     Ok(AUTHENTICATORS
@@ -17,3 +20,26 @@ pub fn authenticator_by_id(store: &dyn Storage, id: u8) -> StdResult<String> {
         Err(error) => Err(error),
     }
 }
+
+pub fn authenticator_tier(store: &dyn Storage, id: u8) -> StdResult<AuthenticatorTier> {
+    // ensure the authenticator exists
+    AUTHENTICATORS.load(store, id)?;
+
+    Ok(AUTHENTICATOR_TIERS
+        .may_load(store, id)?
+        .unwrap_or(AuthenticatorTier::Admin))
+}
+
+pub fn high_risk_msg_types(store: &dyn Storage) -> StdResult<Vec<String>> {
+    Ok(HIGH_RISK_MSG_TYPES.may_load(store)?.unwrap_or_default())
+}
+
+pub fn removal_delay(store: &dyn Storage) -> StdResult<Option<u64>> {
+    REMOVAL_DELAY.may_load(store)
+}
+
+pub fn pending_actions(store: &dyn Storage) -> StdResult<Vec<(u64, PendingAction)>> {
+    PENDING_ACTIONS
+        .range(store, None, None, Order::Ascending)
+        .collect()
+}
@@ -1,4 +1,64 @@
 use crate::auth::Authenticator;
-use cw_storage_plus::Map;
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Timestamp;
+use cw_storage_plus::{Item, Map};
 
 pub const AUTHENTICATORS: Map<u8, Authenticator> = Map::new("authenticators");
+
+/// When each authenticator was added or last replaced. Authenticators without
+/// an entry were added before this was recorded.
+pub const AUTHENTICATORS_ADDED_AT: Map<u8, Timestamp> = Map::new("authenticators_added_at");
+
+#[cw_serde]
+pub enum AuthenticatorTier {
+    Daily,
+    Admin,
+}
+
+/// Authenticators without an entry are treated as `AuthenticatorTier::Admin`.
+pub const AUTHENTICATOR_TIERS: Map<u8, AuthenticatorTier> = Map::new("authenticator_tiers");
+
+/// Message type urls that may only be signed by an admin-tier authenticator,
+/// in addition to the account's own management messages.
+pub const HIGH_RISK_MSG_TYPES: Item<Vec<String>> = Item::new("high_risk_msg_types");
+
+/// Seconds a removal or replacement of an authenticator must wait before it
+/// can be executed. When unset, such changes take effect immediately.
+pub const REMOVAL_DELAY: Item<u64> = Item::new("removal_delay");
+
+/// The longest `REMOVAL_DELAY` that can be set, 30 days.
+pub const MAX_REMOVAL_DELAY: u64 = 30 * 24 * 60 * 60;
+
+#[cw_serde]
+pub enum AuthAction {
+    Remove {
+        id: u8,
+    },
+    Replace {
+        id: u8,
+        authenticator: Authenticator,
+    },
+    UpdateRemovalDelay {
+        seconds: Option<u64>,
+    },
+}
+
+#[cw_serde]
+pub struct PendingAction {
+    pub action: AuthAction,
+    pub executable_at: Timestamp,
+    /// The authenticator removed or replaced by the action, which may not
+    /// cancel it
+    pub target: Option<u8>,
+    /// Authenticators added or replaced at or after this time may not cancel
+    /// the action, so a key can't add a sibling to cancel its own removal
+    pub queued_at: Timestamp,
+}
+
+pub const PENDING_ACTIONS: Map<u64, PendingAction> = Map::new("pending_actions");
+
+pub const NEXT_PENDING_ACTION_ID: Item<u64> = Item::new("next_pending_action_id");
+
+/// Index of the authenticator that signed the tx being executed, recorded by
+/// `before_tx` and cleared by `after_tx`.
+pub const TX_AUTHENTICATOR: Item<u8> = Item::new("tx_authenticator");
@@ -1,6 +1,7 @@
 use std::env;
 
 use crate::error::ContractError;
+use crate::migrations::MIGRATIONS;
 use crate::msg::{InstantiateMsg, MigrateMsg};
 use crate::state::init_auto_increment;
 use crate::state::Config;
@@ -26,6 +27,7 @@ pub fn instantiate(
 }
 
 #[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let report = migration::migrate(deps.storage, CONTRACT_NAME, CONTRACT_VERSION, MIGRATIONS)?;
+    Ok(report.into_response())
 }
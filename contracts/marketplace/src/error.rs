@@ -7,6 +7,9 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Migration(#[from] migration::MigrationError),
+
     #[error("Unauthorized: {message}")]
     Unauthorized { message: String },
 
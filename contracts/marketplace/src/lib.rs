@@ -3,6 +3,7 @@ pub mod error;
 pub mod events;
 pub mod execute;
 pub mod helpers;
+pub mod migrations;
 pub mod msg;
 pub mod offers;
 pub mod query;
@@ -0,0 +1,5 @@
+use migration::Migration;
+
+/// State transforms by the version that introduced them, see
+/// [`migration::migrate`].
+pub const MIGRATIONS: &[Migration] = &[];
@@ -8,4 +8,5 @@ mod test_create_collection_offer;
 mod test_create_listing;
 mod test_create_offer;
 mod test_helpers;
+mod test_migrate;
 mod test_reserved_listing_buy;
@@ -0,0 +1,44 @@
+use cosmwasm_std::testing::{mock_dependencies, mock_env};
+use cosmwasm_std::{from_json, to_json_vec, Addr, Storage};
+use serde_json::Value;
+use xion_nft_marketplace::contract::migrate;
+use xion_nft_marketplace::error::ContractError;
+use xion_nft_marketplace::msg::{MigrateMsg, QueryMsg};
+use xion_nft_marketplace::query::query;
+use xion_nft_marketplace::state::{next_auto_increment, Config};
+
+#[test]
+fn test_migrate_v0_1_0() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+
+    // state as stored by 0.1.0, in its raw JSON form
+    let fixture: Value = serde_json::from_str(include_str!("../fixtures/v0_1_0.json")).unwrap();
+    for item in ["contract_info", "config", "auto_increment"] {
+        deps.storage
+            .set(item.as_bytes(), &to_json_vec(&fixture[item]).unwrap());
+    }
+
+    migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+
+    let version = cw2::get_contract_version(&deps.storage).unwrap();
+    assert_eq!(version.contract, "xion-nft-marketplace");
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+
+    let config: Config<Addr> =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.fee_bps, 250);
+    assert_eq!(config.listing_denom, "uxion");
+    assert_eq!(next_auto_increment(&mut deps.storage).unwrap(), 8);
+}
+
+#[test]
+fn test_migrate_rejects_downgrade() {
+    let mut deps = mock_dependencies();
+    cw2::set_contract_version(&mut deps.storage, "xion-nft-marketplace", "99.0.0").unwrap();
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::Migration(migration::MigrationError::Downgrade { .. })
+    ));
+}
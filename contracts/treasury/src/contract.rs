@@ -1,12 +1,10 @@
 use crate::error::{ContractError, ContractResult};
 use crate::execute::{revoke_allowance, update_fee_config, update_params, withdraw_coins};
 use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
-use crate::{execute, query, CONTRACT_NAME, CONTRACT_VERSION};
-use cosmwasm_std::{
-    entry_point, to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
-};
+use crate::{execute, migrations, query, CONTRACT_NAME, CONTRACT_VERSION};
+use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
 
-#[entry_point]
+#[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
@@ -31,43 +29,105 @@ pub fn instantiate(
     )
 }
 
-#[entry_point]
+#[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
 pub fn execute(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> ContractResult<Response> {
-    match msg {
+    // config changes take effect before any message that may depend on them
+    let applied = execute::apply_due_config_changes(deps.storage, &env)?;
+
+    let response = match msg {
         ExecuteMsg::DeployFeeGrant {
             authz_granter,
             authz_grantee,
-        } => execute::deploy_fee_grant(deps, env, authz_granter, authz_grantee),
+            voucher,
+            bundle,
+        } => execute::deploy_fee_grant(deps, env, authz_granter, authz_grantee, voucher, bundle),
         ExecuteMsg::ProposeAdmin { new_admin } => {
             execute::propose_admin(deps, info, new_admin.into_string())
         }
         ExecuteMsg::AcceptAdmin {} => execute::accept_admin(deps, info),
         ExecuteMsg::CancelProposedAdmin {} => execute::cancel_proposed_admin(deps, info),
+        ExecuteMsg::ProposeRole { role, member } => {
+            execute::propose_role(deps, info, role, member.into_string())
+        }
+        ExecuteMsg::AcceptRole { role } => execute::accept_role(deps, info, role),
+        ExecuteMsg::CancelProposedRole { role, member } => {
+            execute::cancel_proposed_role(deps, info, role, member.into_string())
+        }
+        ExecuteMsg::RevokeRole { role, member } => {
+            execute::revoke_role(deps, info, role, member.into_string())
+        }
         ExecuteMsg::UpdateGrantConfig {
             msg_type_url,
             grant_config,
-        } => execute::update_grant_config(deps, info, msg_type_url, grant_config),
+        } => execute::update_grant_config(deps, env, info, msg_type_url, grant_config),
         ExecuteMsg::RemoveGrantConfig { msg_type_url } => {
-            execute::remove_grant_config(deps, info, msg_type_url)
+            execute::remove_grant_config(deps, env, info, msg_type_url)
+        }
+        ExecuteMsg::UpdateFeeConfig { fee_config } => {
+            update_fee_config(deps, env, info, fee_config)
+        }
+        ExecuteMsg::UpdateConfigDelay { seconds } => {
+            execute::update_config_delay(deps, env, info, seconds)
+        }
+        ExecuteMsg::CancelConfigChange { id } => execute::cancel_config_change(deps, info, id),
+        // due changes were applied above
+        ExecuteMsg::ApplyPendingConfigChanges {} => Ok(Response::new()),
+        ExecuteMsg::UpdateGrantBundle {
+            name,
+            msg_type_urls,
+        } => execute::update_grant_bundle(deps, info, name, msg_type_urls),
+        ExecuteMsg::UpdateFeeTier { name, tier } => {
+            execute::update_fee_tier(deps, env, info, name, tier)
+        }
+        ExecuteMsg::SetGranteeTier { grantee, tier } => {
+            execute::set_grantee_tier(deps, info, grantee, tier)
         }
-        ExecuteMsg::UpdateFeeConfig { fee_config } => update_fee_config(deps, info, fee_config),
         ExecuteMsg::RevokeAllowance { grantee } => revoke_allowance(deps, env, info, grantee),
+        ExecuteMsg::Pause { unpause_at } => execute::pause(deps, env, info, unpause_at),
+        ExecuteMsg::Unpause {} => execute::unpause(deps, info),
+        ExecuteMsg::RevokeAllAllowances { limit } => {
+            execute::revoke_all_allowances(deps, env, info, limit)
+        }
         ExecuteMsg::UpdateParams { params } => update_params(deps, info, params),
-        ExecuteMsg::Withdraw { coins } => withdraw_coins(deps, info, coins),
+        ExecuteMsg::Withdraw { coins } => withdraw_coins(deps, env, info, coins),
+        ExecuteMsg::Deposit {} => execute::deposit(deps, info),
+        ExecuteMsg::RefundSponsor { sponsor, coins } => {
+            execute::refund_sponsor(deps, env, info, sponsor.into_string(), coins)
+        }
+        ExecuteMsg::RefundSponsorsProRata { denom, limit } => {
+            execute::refund_sponsors_pro_rata(deps, env, info, denom, limit)
+        }
+        ExecuteMsg::PruneExpired { limit } => execute::prune_expired(deps, env, info, limit),
+        ExecuteMsg::Reconcile { grantee } => execute::reconcile(deps, env, vec![grantee]),
+        ExecuteMsg::ReconcileBatch { grantees } => execute::reconcile(deps, env, grantees),
+        ExecuteMsg::UpdatePruneBounty { bounty } => {
+            execute::update_prune_bounty(deps, info, bounty)
+        }
+        ExecuteMsg::UpdateSponsorKey { sponsor_key } => {
+            execute::update_sponsor_key(deps, env, info, sponsor_key)
+        }
+        ExecuteMsg::UpdateGrantLimits { limits } => {
+            execute::update_grant_limits(deps, env, info, limits)
+        }
+        ExecuteMsg::UpdateSpendBudget { budget } => {
+            execute::update_spend_budget(deps, env, info, budget)
+        }
         ExecuteMsg::Migrate {
             new_code_id,
             migrate_msg,
         } => execute::migrate(deps, env, info, new_code_id, migrate_msg),
-    }
+    }?;
+
+    Ok(response.add_events(applied))
 }
 
-#[entry_point]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+#[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GrantConfigByTypeUrl { msg_type_url } => to_json_binary(
             &query::grant_config_by_type_url(deps.storage, msg_type_url)?,
@@ -76,14 +136,76 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             to_json_binary(&query::grant_config_type_urls(deps.storage)?)
         }
         QueryMsg::FeeConfig {} => to_json_binary(&query::fee_config(deps.storage)?),
+        QueryMsg::FeeTiers {} => to_json_binary(&query::fee_tiers(deps.storage)?),
+        QueryMsg::ConfigDelay {} => to_json_binary(&query::config_delay(deps.storage)?),
+        QueryMsg::PendingConfigChanges {} => {
+            to_json_binary(&query::pending_config_changes(deps.storage)?)
+        }
+        QueryMsg::GranteeTier { grantee } => {
+            to_json_binary(&query::grantee_tier(deps.storage, grantee)?)
+        }
         QueryMsg::Admin {} => to_json_binary(&query::admin(deps.storage)?),
         QueryMsg::PendingAdmin {} => to_json_binary(&query::pending_admin(deps.storage)?),
         QueryMsg::Params {} => to_json_binary(&query::params(deps.storage)?),
+        QueryMsg::Metadata {} => to_json_binary(&query::metadata(deps.storage)?),
+        QueryMsg::CheckEligibility {
+            granter,
+            grantee,
+            bundle,
+        } => to_json_binary(&query::check_eligibility(
+            deps, &env, granter, grantee, bundle,
+        )?),
+        QueryMsg::GrantBundles {} => to_json_binary(&query::grant_bundles(deps.storage)?),
+        QueryMsg::GrantMsgs {
+            granter,
+            grantee,
+            optional_type_urls,
+            expires_in,
+        } => to_json_binary(&query::grant_msgs(
+            deps.storage,
+            &env,
+            granter,
+            grantee,
+            optional_type_urls,
+            expires_in,
+        )?),
+        QueryMsg::RoleMembers { role } => to_json_binary(&query::role_members(deps.storage, role)?),
+        QueryMsg::PendingRoleMembers { role } => {
+            to_json_binary(&query::pending_role_members(deps.storage, role)?)
+        }
+        QueryMsg::PruneBounty {} => to_json_binary(&query::prune_bounty(deps.storage)?),
+        QueryMsg::Pause {} => to_json_binary(&query::pause(deps.storage)?),
+        QueryMsg::SponsorDeposits { sponsor } => {
+            to_json_binary(&query::sponsor_deposits(deps.storage, sponsor)?)
+        }
+        QueryMsg::TotalDeposits {} => to_json_binary(&query::total_deposits(deps.storage)?),
+        QueryMsg::SponsorKey {} => to_json_binary(&query::sponsor_key(deps.storage)?),
+        QueryMsg::VoucherNonceUsed { expiry, nonce } => {
+            to_json_binary(&query::voucher_nonce_used(deps.storage, expiry, nonce)?)
+        }
+        QueryMsg::GrantLimits {} => to_json_binary(&query::grant_limits(deps.storage)?),
+        QueryMsg::SpendBudget {} => to_json_binary(&query::spend_budget(deps.storage)?),
+        QueryMsg::BudgetUsage {} => to_json_binary(&query::budget_usage(deps.storage, env)?),
+        QueryMsg::ActiveGranteeCount {} => {
+            to_json_binary(&query::active_grantee_count(deps.storage)?)
+        }
+        QueryMsg::GranteeHistory { grantee } => {
+            to_json_binary(&query::grantee_history(deps.storage, grantee)?)
+        }
+        QueryMsg::Grantee { grantee } => to_json_binary(&query::grantee(deps.storage, grantee)?),
+        QueryMsg::GranteeAllowance { grantee } => {
+            to_json_binary(&query::grantee_allowance(deps, &env, grantee)?)
+        }
+        QueryMsg::Grantees { start_after, limit } => {
+            to_json_binary(&query::grantees(deps.storage, start_after, limit)?)
+        }
+        QueryMsg::GranteesByExpiration { start_after, limit } => to_json_binary(
+            &query::grantees_by_expiration(deps.storage, start_after, limit)?,
+        ),
     }
 }
 
-#[entry_point]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    // No state migrations performed, just returned a Response
-    Ok(Response::default())
+#[cfg_attr(not(feature = "library"), cosmwasm_std::entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    migrations::migrate(deps)
 }
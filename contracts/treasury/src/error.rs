@@ -10,6 +10,9 @@ pub enum ContractError {
     #[error(transparent)]
     Decode(#[from] cosmos_sdk_proto::prost::DecodeError),
 
+    #[error(transparent)]
+    Migration(#[from] migration::MigrationError),
+
     #[error(transparent)]
     URLParse(#[from] url::ParseError),
 
@@ -34,11 +37,89 @@ pub enum ContractError {
     #[error("unauthorized")]
     Unauthorized,
 
+    #[error("{member} does not hold role {role}")]
+    RoleNotFound { role: String, member: String },
+
     #[error("grant config for {type_url} not found")]
     GrantConfigNotFound { type_url: String },
 
+    #[error("grant config for {type_url} is used by {used_by}")]
+    GrantConfigInUse { type_url: String, used_by: String },
+
     #[error(transparent)]
     JsonError(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Verification(#[from] cosmwasm_std::VerificationError),
+
+    #[error("grantee cannot receive another fee grant before {next_deploy_at}")]
+    DeployCooldown { next_deploy_at: u64 },
+
+    #[error("grantee has reached the limit of {max} fee grants")]
+    DeployLimitReached { max: u32 },
+
+    #[error("treasury has reached the limit of {max} active grantees")]
+    ActiveGranteeLimitReached { max: u32 },
+
+    #[error("spend budget exhausted for {denom}")]
+    BudgetExhausted { denom: String },
+
+    #[error("allowance has no spend limit and cannot be counted against the spend budget")]
+    UnboundedAllowance,
+
+    #[error("fee tier {name} not found")]
+    FeeTierNotFound { name: String },
+
+    #[error("pending config change {id} not found")]
+    PendingConfigChangeNotFound { id: u64 },
+
+    #[error("grant bundle {name} not found")]
+    GrantBundleNotFound { name: String },
+
+    #[error("config delay exceeds the maximum of {max} seconds")]
+    ConfigDelayTooLong { max: u64 },
+
+    #[error("no funds deposited")]
+    NoFundsDeposited,
+
+    #[error("withdrawal exceeds the unallocated balance of {denom}")]
+    InsufficientUnallocatedFunds { denom: String },
+
+    #[error("refund exceeds the sponsor's share of the remaining {denom}")]
+    RefundExceedsDeposit { denom: String },
+
+    #[error("a pro-rata refund of {denom} is in progress")]
+    RefundInProgress { denom: String },
+
+    #[error("{denom} is refunded more than once")]
+    DuplicateRefundDenom { denom: String },
+
+    #[error("fee grant deployment is paused")]
+    Paused,
+
+    #[error("unpause time must be in the future and can't shorten the current pause")]
+    InvalidUnpauseTime,
+
+    #[error("treasury is not paused")]
+    NotPaused,
+
+    #[error("invalid metadata: {msg}")]
+    InvalidMetadata { msg: String },
+
+    #[error("a sponsor voucher is required")]
+    VoucherRequired,
+
+    #[error("sponsor voucher expired")]
+    VoucherExpired,
+
+    #[error("sponsor voucher nonce {nonce} already used")]
+    VoucherNonceUsed { nonce: u64 },
+
+    #[error("sponsor voucher signature is invalid")]
+    InvalidVoucherSignature,
+
+    #[error("invalid sponsor key: {msg}")]
+    InvalidSponsorKey { msg: String },
 }
 
 #[allow(dead_code)]
@@ -1,11 +1,26 @@
 use crate::error::ContractError::{
-    AuthzGrantMismatch, AuthzGrantNotFound, ConfigurationMismatch, GrantConfigNotFound,
-    Unauthorized,
+    ActiveGranteeLimitReached, AuthzGrantMismatch, AuthzGrantNotFound, BudgetExhausted,
+    ConfigDelayTooLong, ConfigurationMismatch, DeployCooldown, DeployLimitReached,
+    DuplicateRefundDenom, FeeTierNotFound, GrantBundleNotFound, GrantConfigInUse,
+    GrantConfigNotFound, InsufficientUnallocatedFunds, InvalidMetadata, InvalidUnpauseTime,
+    NoFundsDeposited, NotPaused, Paused, PendingConfigChangeNotFound, RefundExceedsDeposit,
+    RefundInProgress, RoleNotFound, Unauthorized, UnboundedAllowance,
 };
 use crate::error::ContractResult;
-use crate::grant::allowance::format_allowance;
-use crate::grant::{FeeConfig, GrantConfig};
-use crate::state::{Params, ADMIN, FEE_CONFIG, GRANT_CONFIGS, PARAMS, PENDING_ADMIN};
+use crate::grant::allowance::{format_allowance, spend_limit};
+use crate::grant::authorization::is_within;
+use crate::grant::{Any, FeeConfig, FeeTier, GrantConfig, GrantStatus};
+use crate::state::{
+    current_budget_usage, is_paused, remove_grantee, save_grantee, ConfigChange, GrantLimits,
+    GranteeHistory, GranteeRecord, Params, ParamsMetadata, PauseState, PendingConfigChange,
+    ProRataRefund, Role, SpendBudget, ACTIVE_GRANTEE_COUNT, ADMIN, BUDGET_USAGE, CONFIG_DELAY,
+    FEE_CONFIG, FEE_CONFIG_VERSION, FEE_TIERS, GRANTEES, GRANTEE_HISTORY, GRANTEE_TIERS,
+    GRANT_BUNDLES, GRANT_CONFIGS, GRANT_LIMITS, MAX_CONFIG_DELAY, METADATA_VERSION,
+    NEXT_CONFIG_CHANGE_ID, PARAMS, PAUSE, PENDING_ADMIN, PENDING_CONFIG_CHANGES, PENDING_ROLES,
+    PRO_RATA_REFUNDS, PRUNE_BOUNTY, PRUNE_CURSOR, ROLES, SPEND_BUDGET, SPONSOR_DEPOSITS,
+    SPONSOR_KEY, TOTAL_DEPOSITS,
+};
+use crate::voucher::{consume_voucher, SponsorKey, Voucher};
 use cosmos_sdk_proto::cosmos::authz::v1beta1::{QueryGrantsRequest, QueryGrantsResponse};
 use cosmos_sdk_proto::cosmos::feegrant::v1beta1::QueryAllowanceRequest;
 use cosmos_sdk_proto::prost::Message;
@@ -13,9 +28,10 @@ use cosmos_sdk_proto::traits::MessageExt;
 use cosmos_sdk_proto::Timestamp;
 use cosmwasm_std::BankMsg::Send;
 use cosmwasm_std::{
-    Addr, AnyMsg, Binary, Coin, CosmosMsg, DepsMut, Env, Event, MessageInfo, Order, Response,
-    WasmMsg,
+    Addr, AnyMsg, Binary, Coin, Coins, CosmosMsg, Deps, DepsMut, Env, Event, MessageInfo, Order,
+    Response, StdResult, Storage, Uint128, WasmMsg,
 };
+use cw_storage_plus::Bound;
 use url::Url;
 
 #[allow(dead_code)]
@@ -44,7 +60,7 @@ pub fn init(
 
     FEE_CONFIG.save(deps.storage, &fee_config)?;
 
-    validate_params(&params)?;
+    validate_params(deps.storage, &params)?;
     PARAMS.save(deps.storage, &params)?;
 
     Ok(Response::new().add_event(
@@ -121,6 +137,108 @@ pub fn cancel_proposed_admin(deps: DepsMut, info: MessageInfo) -> ContractResult
     ))
 }
 
+/// Ensures the sender is the admin or holds the role.
+pub fn assert_role(deps: Deps, sender: &Addr, role: Role) -> ContractResult<()> {
+    if ADMIN.load(deps.storage)?.eq(sender) || ROLES.has(deps.storage, (role.as_str(), sender)) {
+        return Ok(());
+    }
+
+    Err(Unauthorized)
+}
+
+#[allow(dead_code)]
+pub fn propose_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+    member: String,
+) -> ContractResult<Response> {
+    let admin = ADMIN.load(deps.storage)?;
+    if admin != info.sender {
+        return Err(Unauthorized);
+    }
+
+    let validated_member = deps.api.addr_validate(&member)?;
+    PENDING_ROLES.save(deps.storage, (role.as_str(), &validated_member), &true)?;
+
+    Ok(
+        Response::new().add_event(Event::new("proposed_role").add_attributes(vec![
+            ("role", role.as_str().to_string()),
+            ("proposed_member", validated_member.to_string()),
+            ("proposer", admin.to_string()),
+        ])),
+    )
+}
+
+#[allow(dead_code)]
+pub fn accept_role(deps: DepsMut, info: MessageInfo, role: Role) -> ContractResult<Response> {
+    if !PENDING_ROLES.has(deps.storage, (role.as_str(), &info.sender)) {
+        return Err(Unauthorized);
+    }
+
+    PENDING_ROLES.remove(deps.storage, (role.as_str(), &info.sender));
+    ROLES.save(deps.storage, (role.as_str(), &info.sender), &true)?;
+
+    Ok(
+        Response::new().add_event(Event::new("accepted_role").add_attributes(vec![
+            ("role", role.as_str().to_string()),
+            ("member", info.sender.to_string()),
+        ])),
+    )
+}
+
+#[allow(dead_code)]
+pub fn cancel_proposed_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+    member: String,
+) -> ContractResult<Response> {
+    let admin = ADMIN.load(deps.storage)?;
+    if admin != info.sender {
+        return Err(Unauthorized);
+    }
+
+    let member = deps.api.addr_validate(&member)?;
+    PENDING_ROLES.remove(deps.storage, (role.as_str(), &member));
+
+    Ok(
+        Response::new().add_event(Event::new("cancelled_proposed_role").add_attributes(vec![
+            ("role", role.as_str().to_string()),
+            ("member", member.to_string()),
+        ])),
+    )
+}
+
+#[allow(dead_code)]
+pub fn revoke_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+    member: String,
+) -> ContractResult<Response> {
+    let admin = ADMIN.load(deps.storage)?;
+    if admin != info.sender {
+        return Err(Unauthorized);
+    }
+
+    let member = deps.api.addr_validate(&member)?;
+    if !ROLES.has(deps.storage, (role.as_str(), &member)) {
+        return Err(RoleNotFound {
+            role: role.as_str().to_string(),
+            member: member.into_string(),
+        });
+    }
+    ROLES.remove(deps.storage, (role.as_str(), &member));
+
+    Ok(
+        Response::new().add_event(Event::new("revoked_role").add_attributes(vec![
+            ("role", role.as_str().to_string()),
+            ("member", member.to_string()),
+        ])),
+    )
+}
+
 #[allow(dead_code)]
 pub fn migrate(
     deps: DepsMut,
@@ -155,38 +273,31 @@ pub fn migrate(
 #[allow(dead_code)]
 pub fn update_grant_config(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     msg_type_url: String,
     grant_config: GrantConfig,
 ) -> ContractResult<Response> {
-    let admin = ADMIN.load(deps.storage)?;
-    if admin != info.sender {
-        return Err(Unauthorized);
-    }
+    assert_role(deps.as_ref(), &info.sender, Role::ConfigManager)?;
 
-    let existed = GRANT_CONFIGS.has(deps.storage, msg_type_url.clone());
-
-    GRANT_CONFIGS.save(deps.storage, msg_type_url.clone(), &grant_config)?;
-
-    Ok(Response::new().add_event(
-        Event::new("updated_treasury_grant_config").add_attributes(vec![
-            ("msg type url", msg_type_url),
-            ("overwritten", existed.to_string()),
-        ]),
-    ))
+    schedule_config_change(
+        deps,
+        &env,
+        ConfigChange::UpdateGrantConfig {
+            msg_type_url,
+            grant_config,
+        },
+    )
 }
 
 #[allow(dead_code)]
 pub fn remove_grant_config(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     msg_type_url: String,
 ) -> ContractResult<Response> {
-    // Check if the sender is the admin
-    let admin = ADMIN.load(deps.storage)?;
-    if admin != info.sender {
-        return Err(Unauthorized);
-    }
+    assert_role(deps.as_ref(), &info.sender, Role::ConfigManager)?;
 
     // Validate that the key exists
     if !GRANT_CONFIGS.has(deps.storage, msg_type_url.clone()) {
@@ -195,222 +306,2056 @@ pub fn remove_grant_config(
         });
     }
 
-    // Remove the grant config
-    GRANT_CONFIGS.remove(deps.storage, msg_type_url.clone());
-
-    Ok(Response::new().add_event(
-        Event::new("removed_treasury_grant_config")
-            .add_attributes(vec![("msg type url", msg_type_url)]),
-    ))
+    schedule_config_change(deps, &env, ConfigChange::RemoveGrantConfig { msg_type_url })
 }
 
 #[allow(dead_code)]
 pub fn update_fee_config(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     fee_config: FeeConfig,
+) -> ContractResult<Response> {
+    assert_role(deps.as_ref(), &info.sender, Role::ConfigManager)?;
+
+    schedule_config_change(deps, &env, ConfigChange::UpdateFeeConfig { fee_config })
+}
+
+/// Changing the delay is itself subject to the current delay.
+#[allow(dead_code)]
+pub fn update_config_delay(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    seconds: u64,
 ) -> ContractResult<Response> {
     let admin = ADMIN.load(deps.storage)?;
     if admin != info.sender {
         return Err(Unauthorized);
     }
 
-    FEE_CONFIG.save(deps.storage, &fee_config)?;
-
-    Ok(Response::new().add_event(Event::new("updated_treasury_fee_config")))
-}
-
-pub fn validate_params(params: &Params) -> ContractResult<()> {
-    Url::parse(params.redirect_url.as_str())?;
-    Url::parse(params.icon_url.as_str())?;
-    serde_json::from_str::<serde_json::Value>(&params.metadata)?;
-
-    Ok(())
+    schedule_config_change(deps, &env, ConfigChange::UpdateConfigDelay { seconds })
 }
 
 #[allow(dead_code)]
-pub fn update_params(deps: DepsMut, info: MessageInfo, params: Params) -> ContractResult<Response> {
+pub fn cancel_config_change(deps: DepsMut, info: MessageInfo, id: u64) -> ContractResult<Response> {
     let admin = ADMIN.load(deps.storage)?;
     if admin != info.sender {
         return Err(Unauthorized);
     }
 
-    validate_params(&params)?;
-
-    PARAMS.save(deps.storage, &params)?;
+    if !PENDING_CONFIG_CHANGES.has(deps.storage, id) {
+        return Err(PendingConfigChangeNotFound { id });
+    }
+    PENDING_CONFIG_CHANGES.remove(deps.storage, id);
 
-    Ok(Response::new().add_event(Event::new("updated_params")))
+    Ok(Response::new().add_event(
+        Event::new("cancelled_treasury_config_change").add_attribute("id", id.to_string()),
+    ))
 }
 
-#[allow(dead_code)]
-pub fn withdraw_coins(
+/// Applies the change now if there is no config delay, otherwise queues it.
+fn schedule_config_change(
     deps: DepsMut,
-    info: MessageInfo,
-    coins: Vec<Coin>,
+    env: &Env,
+    change: ConfigChange,
 ) -> ContractResult<Response> {
-    let admin = ADMIN.load(deps.storage)?;
-    if admin != info.sender {
-        return Err(Unauthorized);
+    let delay = CONFIG_DELAY.may_load(deps.storage)?.unwrap_or_default();
+    if delay == 0 {
+        let event = apply_config_change(deps.storage, env, change)?;
+        return Ok(Response::new().add_event(event));
     }
+    validate_config_change(deps.storage, &change)?;
 
-    Ok(Response::new().add_message(Send {
-        to_address: info.sender.into_string(),
-        amount: coins,
-    }))
+    let id = NEXT_CONFIG_CHANGE_ID
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    NEXT_CONFIG_CHANGE_ID.save(deps.storage, &(id + 1))?;
+    // delays stored before the maximum existed are capped, so they can still
+    // be lowered
+    let activates_at = delay
+        .min(MAX_CONFIG_DELAY)
+        .checked_mul(1_000_000_000)
+        .and_then(|nanos| env.block.time.nanos().checked_add(nanos))
+        .map(cosmwasm_std::Timestamp::from_nanos)
+        .ok_or(ConfigDelayTooLong {
+            max: MAX_CONFIG_DELAY,
+        })?;
+    PENDING_CONFIG_CHANGES.save(
+        deps.storage,
+        id,
+        &PendingConfigChange {
+            change: change.clone(),
+            activates_at,
+        },
+    )?;
+
+    Ok(Response::new().add_event(
+        Event::new("queued_treasury_config_change").add_attributes(vec![
+            ("id", id.to_string()),
+            ("change", serde_json::to_string(&change)?),
+            ("activates_at", activates_at.seconds().to_string()),
+        ]),
+    ))
 }
 
-#[allow(dead_code)]
-pub fn deploy_fee_grant(
-    deps: DepsMut,
-    env: Env,
-    authz_granter: Addr,
-    authz_grantee: Addr,
-) -> ContractResult<Response> {
-    // iterate through all grant configs to validate user has correct permissions
-    // we must iterate, because calling for the list of grants doesn't return msg_type_urls
-    for key in GRANT_CONFIGS.keys(deps.storage, None, None, Order::Ascending) {
-        let msg_type_url = key?;
-        let grant_config = GRANT_CONFIGS.load(deps.storage, msg_type_url.clone())?;
+/// Applies every queued config change whose activation time has passed, in
+/// the order they were queued. A change that is no longer valid, because of
+/// another change made since it was queued, is dropped.
+pub fn apply_due_config_changes(
+    storage: &mut dyn Storage,
+    env: &Env,
+) -> ContractResult<Vec<Event>> {
+    let due: Vec<(u64, PendingConfigChange)> = PENDING_CONFIG_CHANGES
+        .range(storage, None, None, Order::Ascending)
+        .filter(|item| {
+            item.as_ref()
+                .map_or(true, |(_, pending)| pending.activates_at <= env.block.time)
+        })
+        .collect::<StdResult<_>>()?;
 
-        // check if grant exists on chain
-        let authz_query_msg_bytes = QueryGrantsRequest {
-            granter: authz_granter.to_string(),
-            grantee: authz_grantee.to_string(),
-            msg_type_url: msg_type_url.clone(),
-            pagination: None,
-        }
-        .to_bytes()?;
-        let authz_query_res = deps.querier.query_grpc(
-            String::from("/cosmos.authz.v1beta1.Query/Grants"),
-            Binary::new(authz_query_msg_bytes),
-        )?;
+    let mut events = Vec::with_capacity(due.len());
+    for (id, pending) in due {
+        PENDING_CONFIG_CHANGES.remove(storage, id);
+        let event = match apply_config_change(storage, env, pending.change) {
+            Ok(event) => event,
+            Err(err) => Event::new("rejected_treasury_config_change")
+                .add_attribute("reason", err.to_string()),
+        };
+        events.push(event.add_attribute("id", id.to_string()));
+    }
 
-        let response = QueryGrantsResponse::decode(authz_query_res.as_slice())?;
-        let grants = response.grants;
+    Ok(events)
+}
 
-        if grants.clone().is_empty() {
-            if grant_config.optional {
-                continue;
-            } else {
-                return Err(AuthzGrantNotFound { msg_type_url });
+/// Checks a config change against the current config, before it is queued
+/// and again before it is applied.
+fn validate_config_change(storage: &dyn Storage, change: &ConfigChange) -> ContractResult<()> {
+    match change {
+        ConfigChange::UpdateFeeTier {
+            tier: Some(tier), ..
+        } => {
+            for msg_type_url in tier.optional_grants.iter() {
+                if !GRANT_CONFIGS.has(storage, msg_type_url.clone()) {
+                    return Err(GrantConfigNotFound {
+                        type_url: msg_type_url.clone(),
+                    });
+                }
             }
-        } else {
-            match grants.first() {
-                None => return Err(AuthzGrantNotFound { msg_type_url }),
-                Some(grant) => {
-                    match grant.clone().authorization {
-                        None => return Err(AuthzGrantNotFound { msg_type_url }),
-                        Some(auth) => {
-                            // the authorization must match the one in the config
-                            if grant_config.authorization.ne(&auth.into()) {
-                                return Err(AuthzGrantMismatch);
-                            }
-                        }
-                    }
+        }
+        ConfigChange::UpdateSponsorKey {
+            sponsor_key: Some(key),
+        } => key.validate()?,
+        ConfigChange::UpdateConfigDelay { seconds } if *seconds > MAX_CONFIG_DELAY => {
+            return Err(ConfigDelayTooLong {
+                max: MAX_CONFIG_DELAY,
+            });
+        }
+        ConfigChange::RemoveGrantConfig { msg_type_url } => {
+            for item in FEE_TIERS.range(storage, None, None, Order::Ascending) {
+                let (name, tier) = item?;
+                if tier.optional_grants.contains(msg_type_url) {
+                    return Err(GrantConfigInUse {
+                        type_url: msg_type_url.clone(),
+                        used_by: format!("fee tier {name}"),
+                    });
+                }
+            }
+            for item in GRANT_BUNDLES.range(storage, None, None, Order::Ascending) {
+                let (name, msg_type_urls) = item?;
+                if msg_type_urls.contains(msg_type_url) {
+                    return Err(GrantConfigInUse {
+                        type_url: msg_type_url.clone(),
+                        used_by: format!("grant bundle {name}"),
+                    });
                 }
             }
         }
+        _ => {}
     }
-    // at this point, all the authz grants in the grant_config are verified
 
-    let fee_config = FEE_CONFIG.load(deps.storage)?;
-    // create feegrant, if needed
-    match fee_config.allowance {
-        // this treasury doesn't deploy any fees, and can return
-        None => Ok(Response::new()),
-        // allowance should be stored as a prost proto from the feegrant definition
-        Some(allowance) => {
-            // build the new allowance based on expiration
-            let expiration = match fee_config.expiration {
-                None => None,
-                Some(seconds) => {
-                    let expiration_time = env.block.time.plus_seconds(seconds as u64);
-                    Some(Timestamp {
-                        seconds: expiration_time.seconds() as i64,
-                        nanos: expiration_time.subsec_nanos() as i32,
-                    })
-                }
-            };
+    Ok(())
+}
 
-            let formatted_allowance = format_allowance(
-                allowance,
-                env.contract.address.clone(),
-                authz_grantee.clone(),
-                expiration,
-            )?;
-            let feegrant_msg_bytes =
-                cosmos_sdk_proto::cosmos::feegrant::v1beta1::MsgGrantAllowance {
-                    granter: env.contract.address.clone().into_string(),
-                    grantee: authz_grantee.clone().into_string(),
-                    allowance: Some(formatted_allowance.into()),
+fn apply_config_change(
+    storage: &mut dyn Storage,
+    env: &Env,
+    change: ConfigChange,
+) -> ContractResult<Event> {
+    validate_config_change(storage, &change)?;
+
+    match change {
+        ConfigChange::UpdateGrantConfig {
+            msg_type_url,
+            grant_config,
+        } => {
+            let existed = GRANT_CONFIGS.has(storage, msg_type_url.clone());
+
+            GRANT_CONFIGS.save(storage, msg_type_url.clone(), &grant_config)?;
+
+            Ok(
+                Event::new("updated_treasury_grant_config").add_attributes(vec![
+                    ("msg type url", msg_type_url),
+                    ("overwritten", existed.to_string()),
+                ]),
+            )
+        }
+        ConfigChange::RemoveGrantConfig { msg_type_url } => {
+            // Remove the grant config
+            GRANT_CONFIGS.remove(storage, msg_type_url.clone());
+
+            // and its description, which would no longer pass validate_params
+            if let Some(mut params) = PARAMS.may_load(storage)? {
+                if let Some(mut metadata) = parse_metadata(&params.metadata)? {
+                    if metadata.msg_descriptions.remove(&msg_type_url).is_some() {
+                        params.metadata = serde_json::to_string(&metadata)?;
+                        PARAMS.save(storage, &params)?;
+                    }
                 }
-                .to_bytes()?;
-            let cosmos_feegrant_msg = CosmosMsg::Any(AnyMsg {
-                type_url: "/cosmos.feegrant.v1beta1.MsgGrantAllowance".to_string(),
-                value: feegrant_msg_bytes.into(),
-            });
+            }
 
-            // check to see if the user already has an existing feegrant
-            let feegrant_query_msg_bytes = QueryAllowanceRequest {
-                granter: env.contract.address.to_string(),
-                grantee: authz_grantee.to_string(),
+            Ok(Event::new("removed_treasury_grant_config")
+                .add_attributes(vec![("msg type url", msg_type_url)]))
+        }
+        ConfigChange::UpdateFeeConfig { fee_config } => {
+            FEE_CONFIG.save(storage, &fee_config)?;
+            let version = FEE_CONFIG_VERSION.may_load(storage)?.unwrap_or_default() + 1;
+            FEE_CONFIG_VERSION.save(storage, &version)?;
+
+            Ok(Event::new("updated_treasury_fee_config")
+                .add_attributes(vec![("fee_config_version", version.to_string())]))
+        }
+        ConfigChange::UpdateConfigDelay { seconds } => {
+            CONFIG_DELAY.save(storage, &seconds)?;
+
+            Ok(Event::new("updated_treasury_config_delay")
+                .add_attributes(vec![("seconds", seconds.to_string())]))
+        }
+        ConfigChange::UpdateFeeTier { name, tier } => {
+            match &tier {
+                None => FEE_TIERS.remove(storage, name.clone()),
+                Some(tier) => FEE_TIERS.save(storage, name.clone(), tier)?,
             }
-            .to_bytes()?;
-            let feegrant_query_res = deps
-                .querier
-                .query_grpc(
-                    "/cosmos.feegrant.v1beta1.Query/Allowance".to_string(),
-                    feegrant_query_msg_bytes.into(),
-                )
-                .unwrap_or_else(|_| Binary::default());
 
-            let mut msgs: Vec<CosmosMsg> = Vec::new();
-            if !feegrant_query_res.is_empty() {
-                let feegrant_revoke_msg_bytes =
-                    cosmos_sdk_proto::cosmos::feegrant::v1beta1::MsgRevokeAllowance {
-                        granter: env.contract.address.clone().into_string(),
-                        grantee: authz_grantee.clone().into_string(),
-                    }
-                    .to_bytes()?;
-                let cosmos_revoke_msg = CosmosMsg::Any(AnyMsg {
-                    type_url: "/cosmos.feegrant.v1beta1.MsgRevokeAllowance".to_string(),
-                    value: feegrant_revoke_msg_bytes.into(),
+            Ok(Event::new("updated_fee_tier")
+                .add_attribute("name", name)
+                .add_attribute("removed", tier.is_none().to_string()))
+        }
+        ConfigChange::UpdateSponsorKey { sponsor_key } => {
+            match &sponsor_key {
+                None => SPONSOR_KEY.remove(storage),
+                Some(key) => SPONSOR_KEY.save(storage, key)?,
+            }
+
+            Ok(Event::new("updated_sponsor_key")
+                .add_attributes(vec![("sponsor_key", serde_json::to_string(&sponsor_key)?)]))
+        }
+        ConfigChange::UpdateGrantLimits { limits } => {
+            GRANT_LIMITS.save(storage, &limits)?;
+
+            Ok(Event::new("updated_grant_limits")
+                .add_attributes(vec![("limits", serde_json::to_string(&limits)?)]))
+        }
+        ConfigChange::UpdateSpendBudget { budget } => {
+            match &budget {
+                None => {
+                    SPEND_BUDGET.remove(storage);
+                    BUDGET_USAGE.remove(storage);
+                }
+                Some(budget) => {
+                    // committed spend carries over, so changing the budget
+                    // mid-period doesn't reset it
+                    let usage = current_budget_usage(storage, budget, env.block.time)?;
+                    SPEND_BUDGET.save(storage, budget)?;
+                    BUDGET_USAGE.save(storage, &usage)?;
+                }
+            }
+
+            Ok(Event::new("updated_spend_budget")
+                .add_attributes(vec![("budget", serde_json::to_string(&budget)?)]))
+        }
+    }
+}
+
+pub fn validate_params(storage: &dyn Storage, params: &Params) -> ContractResult<()> {
+    Url::parse(params.redirect_url.as_str())?;
+    Url::parse(params.icon_url.as_str())?;
+
+    if let Some(metadata) = parse_metadata(&params.metadata)? {
+        if metadata.display_name.trim().is_empty() {
+            return Err(InvalidMetadata {
+                msg: "display name is empty".to_string(),
+            });
+        }
+        for url in [&metadata.terms_of_service_url, &metadata.privacy_policy_url]
+            .into_iter()
+            .flatten()
+        {
+            Url::parse(url)?;
+        }
+        for msg_type_url in metadata.msg_descriptions.keys() {
+            if !GRANT_CONFIGS.has(storage, msg_type_url.clone()) {
+                return Err(InvalidMetadata {
+                    msg: format!("no grant config for described msg type {msg_type_url}"),
                 });
-                msgs.push(cosmos_revoke_msg);
             }
-            msgs.push(cosmos_feegrant_msg);
-            Ok(Response::new().add_messages(msgs))
         }
     }
+
+    Ok(())
+}
+
+/// Parses `Params.metadata`, returning `None` for free-form metadata without
+/// a schema version.
+pub fn parse_metadata(metadata: &str) -> ContractResult<Option<ParamsMetadata>> {
+    let value = serde_json::from_str::<serde_json::Value>(metadata)?;
+    let Some(schema_version) = value.get("schema_version") else {
+        return Ok(None);
+    };
+    if schema_version.as_u64() != Some(METADATA_VERSION as u64) {
+        return Err(InvalidMetadata {
+            msg: format!("unsupported metadata schema version {schema_version}"),
+        });
+    }
+
+    Ok(Some(serde_json::from_value(value)?))
 }
 
 #[allow(dead_code)]
-pub fn revoke_allowance(
+pub fn update_params(deps: DepsMut, info: MessageInfo, params: Params) -> ContractResult<Response> {
+    assert_role(deps.as_ref(), &info.sender, Role::ConfigManager)?;
+
+    validate_params(deps.storage, &params)?;
+
+    PARAMS.save(deps.storage, &params)?;
+
+    Ok(Response::new().add_event(Event::new("updated_params")))
+}
+
+#[allow(dead_code)]
+pub fn withdraw_coins(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    grantee: Addr,
+    coins: Vec<Coin>,
 ) -> ContractResult<Response> {
-    let admin = ADMIN.load(deps.storage)?;
-    if admin != info.sender {
-        return Err(Unauthorized);
+    assert_role(deps.as_ref(), &info.sender, Role::Finance)?;
+
+    // sponsor deposits can only leave the treasury as fees or refunds
+    for coin in coins.iter() {
+        let balance = deps
+            .querier
+            .query_balance(&env.contract.address, &coin.denom)?;
+        let deposited = TOTAL_DEPOSITS
+            .may_load(deps.storage, &coin.denom)?
+            .unwrap_or_default();
+        if coin.amount > balance.amount.saturating_sub(deposited) {
+            return Err(InsufficientUnallocatedFunds {
+                denom: coin.denom.clone(),
+            });
+        }
     }
 
-    let feegrant_revoke_msg_bytes =
-        cosmos_sdk_proto::cosmos::feegrant::v1beta1::MsgRevokeAllowance {
-            granter: env.contract.address.into_string(),
-            grantee: grantee.clone().into_string(),
+    Ok(Response::new().add_message(Send {
+        to_address: info.sender.into_string(),
+        amount: coins,
+    }))
+}
+
+/// Records the attached funds as a contribution from the sender.
+#[allow(dead_code)]
+pub fn deposit(deps: DepsMut, info: MessageInfo) -> ContractResult<Response> {
+    if info.funds.is_empty() {
+        return Err(NoFundsDeposited);
+    }
+
+    for coin in info.funds.iter() {
+        if PRO_RATA_REFUNDS.has(deps.storage, &coin.denom) {
+            return Err(RefundInProgress {
+                denom: coin.denom.clone(),
+            });
         }
-        .to_bytes()?;
-    let cosmos_feegrant_revoke_msg = CosmosMsg::Any(AnyMsg {
-        type_url: "/cosmos.feegrant.v1beta1.MsgRevokeAllowance".to_string(),
-        value: feegrant_revoke_msg_bytes.into(),
-    });
+        SPONSOR_DEPOSITS.update(
+            deps.storage,
+            (&coin.denom, &info.sender),
+            |deposited| -> StdResult<_> { Ok(deposited.unwrap_or_default() + coin.amount) },
+        )?;
+        TOTAL_DEPOSITS.update(deps.storage, &coin.denom, |total| -> StdResult<_> {
+            Ok(total.unwrap_or_default() + coin.amount)
+        })?;
+    }
 
-    Ok(Response::new()
-        .add_message(cosmos_feegrant_revoke_msg)
+    Ok(
+        Response::new().add_event(Event::new("sponsor_deposit").add_attributes(vec![
+            ("sponsor", info.sender.into_string()),
+            ("amount", serde_json::to_string(&info.funds)?),
+        ])),
+    )
+}
+
+/// Refunds explicit amounts to a sponsor, out of their share of the
+/// treasury's remaining balance. Fees paid from the treasury reduce every
+/// sponsor's share alike, so a sponsor refunded first can't leave the others
+/// with less than their share.
+#[allow(dead_code)]
+pub fn refund_sponsor(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sponsor: String,
+    coins: Vec<Coin>,
+) -> ContractResult<Response> {
+    assert_role(deps.as_ref(), &info.sender, Role::Finance)?;
+
+    let sponsor = deps.api.addr_validate(&sponsor)?;
+    // each refund is checked against the balance before the message
+    for (i, coin) in coins.iter().enumerate() {
+        if coins[..i].iter().any(|c| c.denom == coin.denom) {
+            return Err(DuplicateRefundDenom {
+                denom: coin.denom.clone(),
+            });
+        }
+    }
+
+    let mut refunds: Vec<Coin> = Vec::new();
+    for coin in coins.iter() {
+        if PRO_RATA_REFUNDS.has(deps.storage, &coin.denom) {
+            return Err(RefundInProgress {
+                denom: coin.denom.clone(),
+            });
+        }
+        let deposited = SPONSOR_DEPOSITS
+            .may_load(deps.storage, (&coin.denom, &sponsor))?
+            .unwrap_or_default();
+        if deposited.is_zero() {
+            return Err(RefundExceedsDeposit {
+                denom: coin.denom.clone(),
+            });
+        }
+        let total = TOTAL_DEPOSITS.load(deps.storage, &coin.denom)?;
+        let balance = deps
+            .querier
+            .query_balance(&env.contract.address, &coin.denom)?
+            .amount;
+        let refundable = balance.min(total);
+        if coin.amount > refundable.multiply_ratio(deposited, total) {
+            return Err(RefundExceedsDeposit {
+                denom: coin.denom.clone(),
+            });
+        }
+        if coin.amount.is_zero() {
+            continue;
+        }
+        // the deposit shrinks by what the refund was worth before fees
+        let reduction = coin.amount.multiply_ratio(total, refundable).min(deposited);
+        reduce_deposit(deps.storage, &sponsor, &coin.denom, reduction)?;
+        refunds.push(coin.clone());
+    }
+
+    // the bank module refuses to send nothing
+    let mut response = Response::new();
+    if !refunds.is_empty() {
+        response = response.add_message(Send {
+            to_address: sponsor.to_string(),
+            amount: refunds.clone(),
+        });
+    }
+    Ok(
+        response.add_event(Event::new("sponsor_refund").add_attributes(vec![
+            ("sponsor", sponsor.into_string()),
+            ("amount", serde_json::to_string(&refunds)?),
+        ])),
+    )
+}
+
+const DEFAULT_REFUND_LIMIT: u32 = 10;
+const MAX_REFUND_LIMIT: u32 = 30;
+
+/// Refunds the treasury's remaining balance of a denom, up to the total
+/// deposited, to every sponsor in proportion to their deposits, and clears
+/// their deposits of it. The refundable amount is fixed by the first call,
+/// and each call pays up to `limit` sponsors. Rounding leftovers stay in the
+/// treasury.
+#[allow(dead_code)]
+pub fn refund_sponsors_pro_rata(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: String,
+    limit: Option<u32>,
+) -> ContractResult<Response> {
+    assert_role(deps.as_ref(), &info.sender, Role::Finance)?;
+
+    let mut balance = deps
+        .querier
+        .query_balance(&env.contract.address, &denom)?
+        .amount;
+    let refund = match PRO_RATA_REFUNDS.may_load(deps.storage, &denom)? {
+        Some(refund) => refund,
+        None => {
+            let total = TOTAL_DEPOSITS
+                .may_load(deps.storage, &denom)?
+                .unwrap_or_default();
+            if total.is_zero() {
+                return Ok(Response::new());
+            }
+            // fees paid from the treasury reduce what is left to refund
+            let refund = ProRataRefund {
+                refundable: balance.min(total),
+                total,
+            };
+            PRO_RATA_REFUNDS.save(deps.storage, &denom, &refund)?;
+            refund
+        }
+    };
+
+    let limit = limit.unwrap_or(DEFAULT_REFUND_LIMIT).min(MAX_REFUND_LIMIT) as usize;
+    // refunded sponsors leave the map, so each batch starts from the top
+    let sponsors: Vec<(Addr, Uint128)> = SPONSOR_DEPOSITS
+        .prefix(&denom)
+        .range(deps.storage, None, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+
+    let mut refunded = Uint128::zero();
+    let mut msgs: Vec<CosmosMsg> = Vec::new();
+    for (sponsor, deposited) in sponsors.iter() {
+        reduce_deposit(deps.storage, sponsor, &denom, *deposited)?;
+        // fees paid since the first batch come out of the last refunds
+        let amount = refund
+            .refundable
+            .multiply_ratio(*deposited, refund.total)
+            .min(balance);
+        balance -= amount;
+        refunded += amount;
+        if !amount.is_zero() {
+            msgs.push(CosmosMsg::Bank(Send {
+                to_address: sponsor.to_string(),
+                amount: vec![Coin::new(amount, denom.clone())],
+            }));
+        }
+    }
+
+    let complete = SPONSOR_DEPOSITS
+        .prefix(&denom)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .next()
+        .is_none();
+    if complete {
+        PRO_RATA_REFUNDS.remove(deps.storage, &denom);
+    }
+
+    Ok(Response::new().add_messages(msgs).add_event(
+        Event::new("sponsor_refund_pro_rata").add_attributes(vec![
+            ("denom", denom),
+            ("refunded", refunded.to_string()),
+            ("count", sponsors.len().to_string()),
+            ("complete", complete.to_string()),
+        ]),
+    ))
+}
+
+fn reduce_deposit(
+    storage: &mut dyn Storage,
+    sponsor: &Addr,
+    denom: &str,
+    amount: Uint128,
+) -> StdResult<()> {
+    let deposited = SPONSOR_DEPOSITS.load(storage, (denom, sponsor))? - amount;
+    if deposited.is_zero() {
+        SPONSOR_DEPOSITS.remove(storage, (denom, sponsor));
+    } else {
+        SPONSOR_DEPOSITS.save(storage, (denom, sponsor), &deposited)?;
+    }
+    let total = TOTAL_DEPOSITS.load(storage, denom)? - amount;
+    if total.is_zero() {
+        TOTAL_DEPOSITS.remove(storage, denom);
+    } else {
+        TOTAL_DEPOSITS.save(storage, denom, &total)?;
+    }
+    Ok(())
+}
+
+#[allow(dead_code)]
+pub fn deploy_fee_grant(
+    mut deps: DepsMut,
+    env: Env,
+    authz_granter: Addr,
+    authz_grantee: Addr,
+    voucher: Option<Voucher>,
+    bundle: Option<String>,
+) -> ContractResult<Response> {
+    if is_paused(deps.storage, env.block.time)? {
+        return Err(Paused);
+    }
+    let voucher_tier = consume_voucher(deps.branch(), &env, &authz_grantee, voucher)?;
+    if let Some(name) = &bundle {
+        if !GRANT_BUNDLES.has(deps.storage, name.clone()) {
+            return Err(GrantBundleNotFound { name: name.clone() });
+        }
+    }
+    let bundle_msg_type_urls = bundle_msg_type_urls(deps.storage, &bundle)?;
+    verify_authz_grants(
+        deps.as_ref(),
+        &authz_granter,
+        &authz_grantee,
+        &bundle_msg_type_urls,
+    )?;
+    // at this point, all the authz grants in the grant_config are verified
+    let required_grants = required_grants(deps.storage, &bundle_msg_type_urls)?;
+
+    let (tier, fee_config) =
+        select_fee_config(deps.as_ref(), &authz_granter, &authz_grantee, voucher_tier)?;
+    // create feegrant, if needed
+    match fee_config.allowance {
+        // this treasury doesn't deploy any fees, and can return
+        None => Ok(Response::new()),
+        // allowance should be stored as a prost proto from the feegrant definition
+        Some(allowance) => {
+            enforce_grant_limits(deps.as_ref(), &env, &authz_grantee)?;
+
+            let (formatted_allowance, expiration_time) =
+                build_allowance(&env, &authz_grantee, allowance, fee_config.expiration)?;
+            commit_to_budget(deps.storage, &env, &formatted_allowance)?;
+            let feegrant_msg_bytes =
+                cosmos_sdk_proto::cosmos::feegrant::v1beta1::MsgGrantAllowance {
+                    granter: env.contract.address.clone().into_string(),
+                    grantee: authz_grantee.clone().into_string(),
+                    allowance: Some(formatted_allowance.into()),
+                }
+                .to_bytes()?;
+            let cosmos_feegrant_msg = CosmosMsg::Any(AnyMsg {
+                type_url: "/cosmos.feegrant.v1beta1.MsgGrantAllowance".to_string(),
+                value: feegrant_msg_bytes.into(),
+            });
+
+            // check to see if the user already has an existing feegrant
+            let mut msgs: Vec<CosmosMsg> = Vec::new();
+            if has_allowance(deps.as_ref(), &env.contract.address, &authz_grantee)? {
+                msgs.push(revoke_allowance_msg(&env.contract.address, &authz_grantee)?);
+            }
+            msgs.push(cosmos_feegrant_msg);
+
+            let record = GranteeRecord {
+                granter: authz_granter,
+                deployed_at: env.block.time,
+                expiration: expiration_time,
+                fee_config_version: FEE_CONFIG_VERSION
+                    .may_load(deps.storage)?
+                    .unwrap_or_default(),
+                tier,
+                bundle,
+                required_grants,
+            };
+            save_grantee(deps.storage, &authz_grantee, &record)?;
+            let deploy_count = GRANTEE_HISTORY
+                .may_load(deps.storage, authz_grantee.clone())?
+                .map_or(0, |history| history.deploy_count);
+            GRANTEE_HISTORY.save(
+                deps.storage,
+                authz_grantee.clone(),
+                &GranteeHistory {
+                    deploy_count: deploy_count + 1,
+                    last_deployed_at: env.block.time,
+                },
+            )?;
+
+            Ok(Response::new().add_messages(msgs).add_event(
+                Event::new("deployed_fee_grant").add_attributes(vec![
+                    ("granter", record.granter.into_string()),
+                    ("grantee", authz_grantee.into_string()),
+                    (
+                        "expiration",
+                        record
+                            .expiration
+                            .map_or("none".to_string(), |e| e.seconds().to_string()),
+                    ),
+                    ("fee_config_version", record.fee_config_version.to_string()),
+                    ("tier", record.tier.unwrap_or("default".to_string())),
+                    ("bundle", record.bundle.unwrap_or("none".to_string())),
+                ]),
+            ))
+        }
+    }
+}
+
+/// Picks the fee config to deploy to a grantee: the tier of a verified
+/// voucher, then the tier assigned to the grantee, then the first tier whose
+/// optional grants the grantee holds, then the default fee config. A tier
+/// assigned to the grantee that has since been removed is ignored.
+pub fn select_fee_config(
+    deps: Deps,
+    authz_granter: &Addr,
+    authz_grantee: &Addr,
+    voucher_tier: Option<String>,
+) -> ContractResult<(Option<String>, FeeConfig)> {
+    if let Some(name) = voucher_tier {
+        let tier = FEE_TIERS
+            .may_load(deps.storage, name.clone())?
+            .ok_or(FeeTierNotFound { name: name.clone() })?;
+        return Ok((Some(name), tier.fee_config));
+    }
+    if let Some(name) = GRANTEE_TIERS.may_load(deps.storage, authz_grantee.clone())? {
+        if let Some(tier) = FEE_TIERS.may_load(deps.storage, name.clone())? {
+            return Ok((Some(name), tier.fee_config));
+        }
+    }
+
+    for item in FEE_TIERS.range(deps.storage, None, None, Order::Ascending) {
+        let (name, tier) = item?;
+        if tier.optional_grants.is_empty() {
+            continue;
+        }
+        let mut covered = true;
+        for msg_type_url in tier.optional_grants.iter() {
+            let grant_config = GRANT_CONFIGS
+                .may_load(deps.storage, msg_type_url.clone())?
+                .ok_or(GrantConfigNotFound {
+                    type_url: msg_type_url.clone(),
+                })?;
+            let status = authz_grant_status(
+                deps,
+                authz_granter,
+                authz_grantee,
+                msg_type_url,
+                &grant_config,
+                &[],
+            )?;
+            if status != GrantStatus::Present {
+                covered = false;
+                break;
+            }
+        }
+        if covered {
+            return Ok((Some(name), tier.fee_config));
+        }
+    }
+
+    Ok((None, FEE_CONFIG.load(deps.storage)?))
+}
+
+#[allow(dead_code)]
+pub fn update_fee_tier(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    name: String,
+    tier: Option<FeeTier>,
+) -> ContractResult<Response> {
+    assert_role(deps.as_ref(), &info.sender, Role::ConfigManager)?;
+
+    schedule_config_change(deps, &env, ConfigChange::UpdateFeeTier { name, tier })
+}
+
+#[allow(dead_code)]
+pub fn set_grantee_tier(
+    deps: DepsMut,
+    info: MessageInfo,
+    grantee: Addr,
+    tier: Option<String>,
+) -> ContractResult<Response> {
+    assert_role(deps.as_ref(), &info.sender, Role::ConfigManager)?;
+
+    match &tier {
+        None => GRANTEE_TIERS.remove(deps.storage, grantee.clone()),
+        Some(name) => {
+            if !FEE_TIERS.has(deps.storage, name.clone()) {
+                return Err(FeeTierNotFound { name: name.clone() });
+            }
+            GRANTEE_TIERS.save(deps.storage, grantee.clone(), name)?
+        }
+    }
+
+    Ok(Response::new().add_event(
+        Event::new("set_grantee_tier")
+            .add_attribute("grantee", grantee)
+            .add_attribute("tier", tier.unwrap_or("default".to_string())),
+    ))
+}
+
+/// The msg type urls of a grant bundle. A bundle removed after a grantee
+/// chose it no longer requires anything.
+pub fn bundle_msg_type_urls(
+    storage: &dyn Storage,
+    bundle: &Option<String>,
+) -> StdResult<Vec<String>> {
+    match bundle {
+        None => Ok(vec![]),
+        Some(name) => Ok(GRANT_BUNDLES
+            .may_load(storage, name.clone())?
+            .unwrap_or_default()),
+    }
+}
+
+#[allow(dead_code)]
+pub fn update_grant_bundle(
+    deps: DepsMut,
+    info: MessageInfo,
+    name: String,
+    msg_type_urls: Option<Vec<String>>,
+) -> ContractResult<Response> {
+    assert_role(deps.as_ref(), &info.sender, Role::ConfigManager)?;
+
+    match &msg_type_urls {
+        None => GRANT_BUNDLES.remove(deps.storage, name.clone()),
+        Some(msg_type_urls) => {
+            for msg_type_url in msg_type_urls {
+                if !GRANT_CONFIGS.has(deps.storage, msg_type_url.clone()) {
+                    return Err(GrantConfigNotFound {
+                        type_url: msg_type_url.clone(),
+                    });
+                }
+            }
+            GRANT_BUNDLES.save(deps.storage, name.clone(), msg_type_urls)?
+        }
+    }
+
+    Ok(
+        Response::new().add_event(Event::new("updated_grant_bundle").add_attributes(vec![
+            ("name", name),
+            (
+                "msg_type_urls",
+                msg_type_urls.map_or("removed".to_string(), |urls| urls.join(",")),
+            ),
+        ])),
+    )
+}
+
+/// Checks the treasury's grant limits before deploying a fee grant.
+fn enforce_grant_limits(deps: Deps, env: &Env, grantee: &Addr) -> ContractResult<()> {
+    let limits = GRANT_LIMITS.may_load(deps.storage)?.unwrap_or_default();
+
+    if let Some(history) = GRANTEE_HISTORY.may_load(deps.storage, grantee.clone())? {
+        if let Some(interval) = limits.min_deploy_interval {
+            let next_deploy_at = history.last_deployed_at.plus_seconds(interval);
+            if env.block.time < next_deploy_at {
+                return Err(DeployCooldown {
+                    next_deploy_at: next_deploy_at.seconds(),
+                });
+            }
+        }
+        if let Some(max) = limits.max_deploys_per_grantee {
+            if history.deploy_count >= max {
+                return Err(DeployLimitReached { max });
+            }
+        }
+    }
+
+    // grantees replacing an existing allowance don't add to the active count
+    if let Some(max) = limits.max_active_grantees {
+        let active = ACTIVE_GRANTEE_COUNT
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+        if active >= max && !GRANTEES.has(deps.storage, grantee.clone()) {
+            return Err(ActiveGranteeLimitReached { max });
+        }
+    }
+
+    Ok(())
+}
+
+#[allow(dead_code)]
+pub fn update_grant_limits(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    limits: GrantLimits,
+) -> ContractResult<Response> {
+    assert_role(deps.as_ref(), &info.sender, Role::ConfigManager)?;
+
+    schedule_config_change(deps, &env, ConfigChange::UpdateGrantLimits { limits })
+}
+
+/// Counts the spend limit of an allowance against the spend budget, if one
+/// is set.
+fn commit_to_budget(storage: &mut dyn Storage, env: &Env, allowance: &Any) -> ContractResult<()> {
+    let Some(budget) = SPEND_BUDGET.may_load(storage)? else {
+        return Ok(());
+    };
+    let spend_limit = spend_limit(allowance)?.ok_or(UnboundedAllowance)?;
+
+    let mut usage = current_budget_usage(storage, &budget, env.block.time)?;
+    let mut committed = Coins::default();
+    for coin in usage.committed {
+        committed.add(coin)?;
+    }
+    for coin in spend_limit {
+        let available = budget
+            .amount
+            .iter()
+            .find(|c| c.denom == coin.denom)
+            .map_or(Uint128::zero(), |c| c.amount);
+        committed.add(coin.clone())?;
+        if committed.amount_of(&coin.denom) > available {
+            return Err(BudgetExhausted { denom: coin.denom });
+        }
+    }
+    usage.committed = committed.into_vec();
+    BUDGET_USAGE.save(storage, &usage)?;
+
+    Ok(())
+}
+
+#[allow(dead_code)]
+pub fn update_spend_budget(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    budget: Option<SpendBudget>,
+) -> ContractResult<Response> {
+    assert_role(deps.as_ref(), &info.sender, Role::Finance)?;
+
+    schedule_config_change(deps, &env, ConfigChange::UpdateSpendBudget { budget })
+}
+
+/// Verifies the grantee holds an authz grant from the granter matching each
+/// of the treasury's required grant configs and those in the bundle.
+pub fn verify_authz_grants(
+    deps: Deps,
+    authz_granter: &Addr,
+    authz_grantee: &Addr,
+    bundle: &[String],
+) -> ContractResult<()> {
+    // iterate through all grant configs to validate user has correct permissions
+    // we must iterate, because calling for the list of grants doesn't return msg_type_urls
+    for key in GRANT_CONFIGS.keys(deps.storage, None, None, Order::Ascending) {
+        let msg_type_url = key?;
+        let grant_config = GRANT_CONFIGS.load(deps.storage, msg_type_url.clone())?;
+
+        match authz_grant_status(
+            deps,
+            authz_granter,
+            authz_grantee,
+            &msg_type_url,
+            &grant_config,
+            bundle,
+        )? {
+            GrantStatus::Present | GrantStatus::MissingOptional => continue,
+            GrantStatus::Missing => return Err(AuthzGrantNotFound { msg_type_url }),
+            GrantStatus::Mismatched => return Err(AuthzGrantMismatch),
+        }
+    }
+    // at this point, all the authz grants in the grant_config are verified
+
+    Ok(())
+}
+
+/// The msg type urls of the grant configs a grantee must hold authz grants
+/// for: the non-optional ones and those in its bundle.
+pub fn required_grants(storage: &dyn Storage, bundle: &[String]) -> StdResult<Vec<String>> {
+    GRANT_CONFIGS
+        .range(storage, None, None, Order::Ascending)
+        .filter_map(|item| match item {
+            Ok((msg_type_url, grant_config))
+                if !grant_config.optional || bundle.contains(&msg_type_url) =>
+            {
+                Some(Ok(msg_type_url))
+            }
+            Ok(_) => None,
+            Err(err) => Some(Err(err)),
+        })
+        .collect()
+}
+
+/// The authz grants a deployed grantee was required to hold that it no
+/// longer holds at all. Configs removed since the allowance was deployed
+/// are skipped, and a grant that no longer fits a changed config still
+/// counts as held, so config changes alone never revoke an allowance.
+pub fn missing_required_grants(
+    deps: Deps,
+    grantee: &Addr,
+    record: &GranteeRecord,
+) -> ContractResult<Vec<String>> {
+    let mut missing = Vec::new();
+    for msg_type_url in record.required_grants.iter() {
+        let Some(grant_config) = GRANT_CONFIGS.may_load(deps.storage, msg_type_url.clone())? else {
+            continue;
+        };
+        match authz_grant_status(
+            deps,
+            &record.granter,
+            grantee,
+            msg_type_url,
+            &grant_config,
+            &record.required_grants,
+        )? {
+            GrantStatus::Missing | GrantStatus::MissingOptional => {
+                missing.push(msg_type_url.clone())
+            }
+            GrantStatus::Present | GrantStatus::Mismatched => {}
+        }
+    }
+
+    Ok(missing)
+}
+
+/// Checks the grantee's authz grant from the granter for a single grant
+/// config. Optional configs in the grantee's bundle are required.
+pub fn authz_grant_status(
+    deps: Deps,
+    authz_granter: &Addr,
+    authz_grantee: &Addr,
+    msg_type_url: &str,
+    grant_config: &GrantConfig,
+    bundle: &[String],
+) -> ContractResult<GrantStatus> {
+    // check if grant exists on chain
+    let authz_query_msg_bytes = QueryGrantsRequest {
+        granter: authz_granter.to_string(),
+        grantee: authz_grantee.to_string(),
+        msg_type_url: msg_type_url.to_string(),
+        pagination: None,
+    }
+    .to_bytes()?;
+    // the chain errors when there is no grant for the msg type url
+    let authz_query_res = deps
+        .querier
+        .query_grpc(
+            String::from("/cosmos.authz.v1beta1.Query/Grants"),
+            Binary::new(authz_query_msg_bytes),
+        )
+        .unwrap_or_else(|_| Binary::default());
+
+    let response = QueryGrantsResponse::decode(authz_query_res.as_slice())?;
+
+    match response.grants.first() {
+        None if grant_config.optional && !bundle.iter().any(|t| t == msg_type_url) => {
+            Ok(GrantStatus::MissingOptional)
+        }
+        None => Ok(GrantStatus::Missing),
+        Some(grant) => match grant.clone().authorization {
+            None => Ok(GrantStatus::Missing),
+            // the authorization must be equal to or narrower than the one in
+            // the config
+            Some(auth) => match is_within(&grant_config.authorization, &auth.into())? {
+                true => Ok(GrantStatus::Present),
+                false => Ok(GrantStatus::Mismatched),
+            },
+        },
+    }
+}
+
+/// The allowance to deploy to a grantee now, with its expiration.
+pub fn build_allowance(
+    env: &Env,
+    grantee: &Addr,
+    allowance: Any,
+    expiration: Option<u32>,
+) -> ContractResult<(Any, Option<cosmwasm_std::Timestamp>)> {
+    // build the new allowance based on expiration
+    let expiration_time = expiration.map(|seconds| env.block.time.plus_seconds(seconds as u64));
+    let expiration = expiration_time.map(|expiration_time| Timestamp {
+        seconds: expiration_time.seconds() as i64,
+        nanos: expiration_time.subsec_nanos() as i32,
+    });
+
+    let formatted_allowance = format_allowance(
+        allowance,
+        env.contract.address.clone(),
+        grantee.clone(),
+        expiration,
+    )?;
+
+    Ok((formatted_allowance, expiration_time))
+}
+
+/// Whether the treasury currently has a fee allowance deployed to the grantee.
+pub fn has_allowance(deps: Deps, granter: &Addr, grantee: &Addr) -> ContractResult<bool> {
+    let feegrant_query_msg_bytes = QueryAllowanceRequest {
+        granter: granter.to_string(),
+        grantee: grantee.to_string(),
+    }
+    .to_bytes()?;
+    let feegrant_query_res = deps
+        .querier
+        .query_grpc(
+            "/cosmos.feegrant.v1beta1.Query/Allowance".to_string(),
+            feegrant_query_msg_bytes.into(),
+        )
+        .unwrap_or_else(|_| Binary::default());
+
+    Ok(!feegrant_query_res.is_empty())
+}
+
+pub fn revoke_allowance_msg(granter: &Addr, grantee: &Addr) -> ContractResult<CosmosMsg> {
+    let feegrant_revoke_msg_bytes =
+        cosmos_sdk_proto::cosmos::feegrant::v1beta1::MsgRevokeAllowance {
+            granter: granter.to_string(),
+            grantee: grantee.to_string(),
+        }
+        .to_bytes()?;
+
+    Ok(CosmosMsg::Any(AnyMsg {
+        type_url: "/cosmos.feegrant.v1beta1.MsgRevokeAllowance".to_string(),
+        value: feegrant_revoke_msg_bytes.into(),
+    }))
+}
+
+#[allow(dead_code)]
+pub fn revoke_allowance(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    grantee: Addr,
+) -> ContractResult<Response> {
+    assert_role(deps.as_ref(), &info.sender, Role::Operator)?;
+
+    remove_grantee(deps.storage, &grantee)?;
+
+    let cosmos_feegrant_revoke_msg = revoke_allowance_msg(&env.contract.address, &grantee)?;
+
+    Ok(Response::new()
+        .add_message(cosmos_feegrant_revoke_msg)
         .add_event(
             Event::new("revoked_treasury_allowance")
                 .add_attributes(vec![("grantee", grantee.into_string())]),
         ))
 }
+
+/// Stops fee grant deployment, until `unpause_at` if set. A later pause
+/// replaces the unpause time.
+#[allow(dead_code)]
+pub fn pause(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    unpause_at: Option<cosmwasm_std::Timestamp>,
+) -> ContractResult<Response> {
+    assert_role(deps.as_ref(), &info.sender, Role::Guardian)?;
+
+    if unpause_at.is_some_and(|unpause_at| unpause_at <= env.block.time) {
+        return Err(InvalidUnpauseTime);
+    }
+    // shortening a pause would let the guardian unpause
+    if is_paused(deps.storage, env.block.time)? && ADMIN.load(deps.storage)? != info.sender {
+        let current = PAUSE.load(deps.storage)?.unpause_at;
+        let shortened = match (current, unpause_at) {
+            (None, Some(_)) => true,
+            (Some(current), Some(unpause_at)) => unpause_at < current,
+            (_, None) => false,
+        };
+        if shortened {
+            return Err(InvalidUnpauseTime);
+        }
+    }
+
+    PAUSE.save(
+        deps.storage,
+        &PauseState {
+            paused_at: env.block.time,
+            unpause_at,
+        },
+    )?;
+
+    Ok(
+        Response::new().add_event(Event::new("paused_treasury").add_attributes(vec![
+            ("sender", info.sender.into_string()),
+            (
+                "unpause_at",
+                unpause_at.map_or("none".to_string(), |t| t.seconds().to_string()),
+            ),
+        ])),
+    )
+}
+
+#[allow(dead_code)]
+pub fn unpause(deps: DepsMut, info: MessageInfo) -> ContractResult<Response> {
+    let admin = ADMIN.load(deps.storage)?;
+    if admin != info.sender {
+        return Err(Unauthorized);
+    }
+
+    PAUSE.remove(deps.storage);
+
+    Ok(Response::new().add_event(Event::new("unpaused_treasury")))
+}
+
+/// Revokes up to `limit` deployed allowances while the treasury is paused.
+/// Only the admin may, since a guardian's pause is meant to be reversible.
+#[allow(dead_code)]
+pub fn revoke_all_allowances(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> ContractResult<Response> {
+    let admin = ADMIN.load(deps.storage)?;
+    if admin != info.sender {
+        return Err(Unauthorized);
+    }
+    if !is_paused(deps.storage, env.block.time)? {
+        return Err(NotPaused);
+    }
+
+    let limit = limit.unwrap_or(DEFAULT_PRUNE_LIMIT).min(MAX_PRUNE_LIMIT) as usize;
+    // revoked grantees leave the registry, so each batch starts from the top
+    let grantees: Vec<Addr> = GRANTEES
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+
+    let mut msgs: Vec<CosmosMsg> = Vec::new();
+    for grantee in grantees.iter() {
+        remove_grantee(deps.storage, grantee)?;
+        if has_allowance(deps.as_ref(), &env.contract.address, grantee)? {
+            msgs.push(revoke_allowance_msg(&env.contract.address, grantee)?);
+        }
+    }
+
+    Ok(Response::new().add_messages(msgs).add_event(
+        Event::new("revoked_all_treasury_allowances").add_attributes(vec![
+            ("count", grantees.len().to_string()),
+            (
+                "remaining",
+                ACTIVE_GRANTEE_COUNT
+                    .may_load(deps.storage)?
+                    .unwrap_or_default()
+                    .to_string(),
+            ),
+        ]),
+    ))
+}
+
+const DEFAULT_PRUNE_LIMIT: u32 = 10;
+const MAX_PRUNE_LIMIT: u32 = 30;
+
+#[allow(dead_code)]
+pub fn prune_expired(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> ContractResult<Response> {
+    let limit = limit.unwrap_or(DEFAULT_PRUNE_LIMIT).min(MAX_PRUNE_LIMIT) as usize;
+
+    // grantees whose allowance expiration has passed, soonest first
+    let expired: Vec<Addr> = GRANTEES
+        .idx
+        .expiration
+        .range(deps.storage, None, None, Order::Ascending)
+        .take_while(|item| {
+            item.as_ref().map_or(true, |(_, record)| {
+                record.expiration.is_some_and(|e| e <= env.block.time)
+            })
+        })
+        .take(limit)
+        .map(|item| item.map(|(grantee, _)| grantee))
+        .collect::<StdResult<_>>()?;
+
+    // with the remaining budget, continue checking grantees for authz grants
+    // that have been revoked, resuming from where the last call stopped
+    let mut pruned = expired.clone();
+    let mut skipped: Vec<Addr> = Vec::new();
+    let remaining = limit - expired.len();
+    if remaining > 0 {
+        let cursor = PRUNE_CURSOR.may_load(deps.storage)?;
+        let checked: Vec<(Addr, GranteeRecord)> = GRANTEES
+            .range(
+                deps.storage,
+                cursor.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(remaining)
+            .collect::<StdResult<_>>()?;
+
+        match checked.last() {
+            Some((last, _)) if checked.len() == remaining => {
+                PRUNE_CURSOR.save(deps.storage, last)?
+            }
+            // reached the end of the registry, start over on the next call
+            _ => PRUNE_CURSOR.remove(deps.storage),
+        }
+
+        for (grantee, record) in checked {
+            if pruned.contains(&grantee) {
+                continue;
+            }
+            match missing_required_grants(deps.as_ref(), &grantee, &record) {
+                Ok(missing) if missing.is_empty() => {}
+                Ok(_) => pruned.push(grantee),
+                // a grantee that can't be checked mustn't hold up the rest
+                Err(_) => skipped.push(grantee),
+            }
+        }
+    }
+
+    let mut msgs: Vec<CosmosMsg> = Vec::new();
+    for grantee in pruned.iter() {
+        remove_grantee(deps.storage, grantee)?;
+        // the chain may have already removed an expired allowance
+        if has_allowance(deps.as_ref(), &env.contract.address, grantee)? {
+            msgs.push(revoke_allowance_msg(&env.contract.address, grantee)?);
+        }
+    }
+
+    // grantees can revoke their own authz grants at will, so only expired
+    // allowances earn the bounty
+    if let Some(bounty) = PRUNE_BOUNTY.may_load(deps.storage)? {
+        if !expired.is_empty() {
+            msgs.push(CosmosMsg::Bank(Send {
+                to_address: info.sender.into_string(),
+                amount: vec![Coin {
+                    denom: bounty.denom,
+                    amount: bounty.amount * Uint128::from(expired.len() as u128),
+                }],
+            }));
+        }
+    }
+
+    Ok(Response::new().add_messages(msgs).add_event(
+        Event::new("pruned_fee_grants").add_attributes(vec![
+            ("count", pruned.len().to_string()),
+            ("expired", expired.len().to_string()),
+            (
+                "grantees",
+                pruned
+                    .iter()
+                    .map(Addr::as_str)
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+            (
+                "skipped",
+                skipped
+                    .iter()
+                    .map(Addr::as_str)
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+        ]),
+    ))
+}
+
+/// Re-checks the authz grants of deployed fee grants, revoking the allowance
+/// of any grantee missing a grant it was required to hold at deployment.
+#[allow(dead_code)]
+pub fn reconcile(deps: DepsMut, env: Env, grantees: Vec<Addr>) -> ContractResult<Response> {
+    let mut msgs: Vec<CosmosMsg> = Vec::new();
+    let mut events: Vec<Event> = Vec::new();
+    let mut skipped: Vec<Addr> = Vec::new();
+
+    for grantee in grantees {
+        let Some(record) = GRANTEES.may_load(deps.storage, grantee.clone())? else {
+            events.push(
+                Event::new("reconciled_fee_grant")
+                    .add_attribute("grantee", grantee)
+                    .add_attribute("result", "not_found"),
+            );
+            continue;
+        };
+
+        let mut event = Event::new("reconciled_fee_grant").add_attribute("grantee", &grantee);
+        // a grantee that can't be checked mustn't hold up the rest of a batch
+        let missing = match missing_required_grants(deps.as_ref(), &grantee, &record) {
+            Ok(missing) => missing,
+            Err(_) => {
+                events.push(event.add_attribute("result", "skipped"));
+                skipped.push(grantee);
+                continue;
+            }
+        };
+        let reasons: Vec<String> = missing
+            .into_iter()
+            .map(|msg_type_url| format!("missing:{msg_type_url}"))
+            .collect();
+
+        if reasons.is_empty() {
+            event = event.add_attribute("result", "valid");
+        } else {
+            remove_grantee(deps.storage, &grantee)?;
+            if has_allowance(deps.as_ref(), &env.contract.address, &grantee)? {
+                msgs.push(revoke_allowance_msg(&env.contract.address, &grantee)?);
+            }
+            event = event
+                .add_attribute("result", "revoked")
+                .add_attribute("reasons", reasons.join(","));
+        }
+        events.push(event);
+    }
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_events(events)
+        .add_event(
+            Event::new("reconciled_fee_grants").add_attribute(
+                "skipped",
+                skipped
+                    .iter()
+                    .map(Addr::as_str)
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+        ))
+}
+
+#[allow(dead_code)]
+pub fn update_prune_bounty(
+    deps: DepsMut,
+    info: MessageInfo,
+    bounty: Option<Coin>,
+) -> ContractResult<Response> {
+    assert_role(deps.as_ref(), &info.sender, Role::Finance)?;
+
+    match &bounty {
+        None => PRUNE_BOUNTY.remove(deps.storage),
+        Some(coin) => PRUNE_BOUNTY.save(deps.storage, coin)?,
+    }
+
+    Ok(
+        Response::new().add_event(Event::new("updated_prune_bounty").add_attributes(vec![(
+            "bounty",
+            bounty.map_or("none".to_string(), |coin| coin.to_string()),
+        )])),
+    )
+}
+
+#[allow(dead_code)]
+pub fn update_sponsor_key(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sponsor_key: Option<SponsorKey>,
+) -> ContractResult<Response> {
+    assert_role(deps.as_ref(), &info.sender, Role::ConfigManager)?;
+
+    schedule_config_change(deps, &env, ConfigChange::UpdateSponsorKey { sponsor_key })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::ContractError;
+    use crate::execute::{
+        accept_role, apply_due_config_changes, assert_role, cancel_config_change,
+        cancel_proposed_role, deploy_fee_grant, deposit, parse_metadata, pause, propose_role,
+        refund_sponsor, refund_sponsors_pro_rata, remove_grant_config, revoke_allowance,
+        revoke_role, select_fee_config, set_grantee_tier, unpause, update_config_delay,
+        update_fee_config, update_fee_tier, update_grant_bundle, update_grant_limits,
+        update_spend_budget, update_sponsor_key, validate_params, withdraw_coins,
+    };
+    use crate::grant::{FeeConfig, FeeTier, GrantConfig};
+    use crate::state::{
+        GrantLimits, Params, Role, SpendBudget, ADMIN, CONFIG_DELAY, FEE_CONFIG, FEE_TIERS,
+        GRANT_CONFIGS, GRANT_LIMITS, MAX_CONFIG_DELAY, PARAMS, PENDING_CONFIG_CHANGES,
+        PRO_RATA_REFUNDS, ROLES, SPEND_BUDGET, SPONSOR_DEPOSITS, SPONSOR_KEY, TOTAL_DEPOSITS,
+    };
+    use crate::voucher::SponsorKey;
+    use cosmwasm_std::testing::{message_info, mock_dependencies, mock_env};
+    use cosmwasm_std::{coins, from_json, BankMsg, Coin, CosmosMsg, Order, Response, Uint128};
+
+    fn fee_config(expiration: u32) -> FeeConfig {
+        from_json(format!(
+            r#"{{"description":"test","allowance":null,"expiration":{expiration}}}"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_delayed_config_change() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let admin = deps.api.addr_make("admin");
+        ADMIN.save(deps.as_mut().storage, &admin).unwrap();
+        FEE_CONFIG
+            .save(deps.as_mut().storage, &fee_config(1))
+            .unwrap();
+        CONFIG_DELAY.save(deps.as_mut().storage, &100).unwrap();
+
+        let info = message_info(&admin, &[]);
+        update_fee_config(deps.as_mut(), env.clone(), info.clone(), fee_config(2)).unwrap();
+        update_fee_config(deps.as_mut(), env.clone(), info.clone(), fee_config(3)).unwrap();
+        cancel_config_change(deps.as_mut(), info, 1).unwrap();
+
+        // nothing changes before the delay has passed
+        env.block.time = env.block.time.plus_seconds(99);
+        assert!(apply_due_config_changes(deps.as_mut().storage, &env)
+            .unwrap()
+            .is_empty());
+        assert_eq!(FEE_CONFIG.load(&deps.storage).unwrap(), fee_config(1));
+
+        env.block.time = env.block.time.plus_seconds(1);
+        let events = apply_due_config_changes(deps.as_mut().storage, &env).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(FEE_CONFIG.load(&deps.storage).unwrap(), fee_config(2));
+        assert!(PENDING_CONFIG_CHANGES.is_empty(&deps.storage));
+
+        // limits, keys, budgets and tiers wait for the delay too
+        let info = message_info(&admin, &[]);
+        let limits = GrantLimits {
+            max_active_grantees: Some(1),
+            ..Default::default()
+        };
+        update_grant_limits(deps.as_mut(), env.clone(), info.clone(), limits.clone()).unwrap();
+        update_sponsor_key(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            Some(SponsorKey::Ed25519 {
+                pubkey: vec![1; 32].into(),
+            }),
+        )
+        .unwrap();
+        update_spend_budget(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            Some(SpendBudget {
+                amount: coins(100, "uxion"),
+                period: 0,
+            }),
+        )
+        .unwrap();
+        update_fee_tier(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            "gold".to_string(),
+            Some(FeeTier {
+                fee_config: fee_config(3),
+                optional_grants: vec![],
+            }),
+        )
+        .unwrap();
+        assert!(GRANT_LIMITS.may_load(&deps.storage).unwrap().is_none());
+        assert!(SPONSOR_KEY.may_load(&deps.storage).unwrap().is_none());
+        assert!(SPEND_BUDGET.may_load(&deps.storage).unwrap().is_none());
+        assert!(FEE_TIERS.is_empty(&deps.storage));
+
+        env.block.time = env.block.time.plus_seconds(100);
+        let events = apply_due_config_changes(deps.as_mut().storage, &env).unwrap();
+        assert_eq!(events.len(), 4);
+        assert_eq!(GRANT_LIMITS.load(&deps.storage).unwrap(), limits);
+        assert!(SPONSOR_KEY.may_load(&deps.storage).unwrap().is_some());
+        assert!(SPEND_BUDGET.may_load(&deps.storage).unwrap().is_some());
+        assert!(FEE_TIERS.has(&deps.storage, "gold".to_string()));
+
+        let info = message_info(&admin, &[]);
+        let err = update_config_delay(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            MAX_CONFIG_DELAY + 1,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::ConfigDelayTooLong { .. }));
+
+        // a delay stored before the maximum existed is capped rather than overflowing
+        CONFIG_DELAY.save(deps.as_mut().storage, &u64::MAX).unwrap();
+        update_config_delay(deps.as_mut(), env.clone(), info, 100).unwrap();
+        let (_, pending) = PENDING_CONFIG_CHANGES
+            .range(&deps.storage, None, None, Order::Ascending)
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            pending.activates_at,
+            env.block.time.plus_seconds(MAX_CONFIG_DELAY)
+        );
+        env.block.time = pending.activates_at;
+        apply_due_config_changes(deps.as_mut().storage, &env).unwrap();
+        assert_eq!(CONFIG_DELAY.load(&deps.storage).unwrap(), 100);
+    }
+
+    fn grant_config(optional: bool) -> GrantConfig {
+        from_json(format!(
+            r#"{{"description":"test","authorization":{{"type_url":"/cosmos.authz.v1beta1.GenericAuthorization","value":""}},"optional":{optional}}}"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_fee_tier_grant_configs() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let admin = deps.api.addr_make("admin");
+        let grantee = deps.api.addr_make("grantee");
+        let info = message_info(&admin, &[]);
+        ADMIN.save(deps.as_mut().storage, &admin).unwrap();
+        FEE_CONFIG
+            .save(deps.as_mut().storage, &fee_config(1))
+            .unwrap();
+        let send = "/cosmos.bank.v1beta1.MsgSend".to_string();
+        let delegate = "/cosmos.staking.v1beta1.MsgDelegate".to_string();
+        for msg_type_url in [&send, &delegate] {
+            GRANT_CONFIGS
+                .save(
+                    deps.as_mut().storage,
+                    msg_type_url.clone(),
+                    &grant_config(true),
+                )
+                .unwrap();
+        }
+        let tier = |optional_grants: Vec<String>| FeeTier {
+            fee_config: fee_config(2),
+            optional_grants,
+        };
+
+        let err = update_fee_tier(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            "gold".to_string(),
+            Some(tier(vec!["/unknown".to_string()])),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::GrantConfigNotFound { .. }));
+
+        // configs used by a tier or bundle can't be removed
+        update_fee_tier(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            "gold".to_string(),
+            Some(tier(vec![send.clone()])),
+        )
+        .unwrap();
+        let err = remove_grant_config(deps.as_mut(), env.clone(), info.clone(), send.clone())
+            .unwrap_err();
+        assert!(
+            matches!(err, ContractError::GrantConfigInUse { used_by, .. } if used_by == "fee tier gold")
+        );
+        update_grant_bundle(
+            deps.as_mut(),
+            info.clone(),
+            "staking".to_string(),
+            Some(vec![delegate.clone()]),
+        )
+        .unwrap();
+        let err = remove_grant_config(deps.as_mut(), env.clone(), info.clone(), delegate.clone())
+            .unwrap_err();
+        assert!(
+            matches!(err, ContractError::GrantConfigInUse { used_by, .. } if used_by == "grant bundle staking")
+        );
+
+        // a queued removal is dropped if a tier queued before it uses the config
+        update_fee_tier(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            "gold".to_string(),
+            Some(tier(vec![])),
+        )
+        .unwrap();
+        CONFIG_DELAY.save(deps.as_mut().storage, &100).unwrap();
+        update_fee_tier(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            "silver".to_string(),
+            Some(tier(vec![send.clone()])),
+        )
+        .unwrap();
+        remove_grant_config(deps.as_mut(), env.clone(), info.clone(), send.clone()).unwrap();
+        env.block.time = env.block.time.plus_seconds(100);
+        let events = apply_due_config_changes(deps.as_mut().storage, &env).unwrap();
+        assert_eq!(events[0].ty, "updated_fee_tier");
+        assert_eq!(events[1].ty, "rejected_treasury_config_change");
+        assert!(GRANT_CONFIGS.has(&deps.storage, send));
+        CONFIG_DELAY.remove(deps.as_mut().storage);
+
+        // a grantee whose tier is removed falls back to the default
+        set_grantee_tier(
+            deps.as_mut(),
+            info.clone(),
+            grantee.clone(),
+            Some("gold".to_string()),
+        )
+        .unwrap();
+        assert_eq!(
+            select_fee_config(deps.as_ref(), &admin, &grantee, None).unwrap(),
+            (Some("gold".to_string()), fee_config(2))
+        );
+        update_fee_tier(deps.as_mut(), env, info, "gold".to_string(), None).unwrap();
+        assert_eq!(
+            select_fee_config(deps.as_ref(), &admin, &grantee, None).unwrap(),
+            (None, fee_config(1))
+        );
+        // but a voucher's tier must exist
+        let err = select_fee_config(deps.as_ref(), &admin, &grantee, Some("gold".to_string()))
+            .unwrap_err();
+        assert!(matches!(err, ContractError::FeeTierNotFound { .. }));
+    }
+
+    #[test]
+    fn test_sponsor_deposits() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let admin = deps.api.addr_make("admin");
+        let sponsor_a = deps.api.addr_make("sponsor_a");
+        let sponsor_b = deps.api.addr_make("sponsor_b");
+        ADMIN.save(deps.as_mut().storage, &admin).unwrap();
+
+        deposit(
+            deps.as_mut(),
+            message_info(&sponsor_a, &coins(300, "uxion")),
+        )
+        .unwrap();
+        deposit(
+            deps.as_mut(),
+            message_info(&sponsor_b, &coins(100, "uxion")),
+        )
+        .unwrap();
+        // 100 uxion sent without a deposit, and 200 spent on fees
+        deps.querier
+            .bank
+            .update_balance(&env.contract.address, coins(300, "uxion"));
+
+        // only funds beyond the deposits can be withdrawn
+        let admin_info = message_info(&admin, &[]);
+        let err = withdraw_coins(
+            deps.as_mut(),
+            env.clone(),
+            admin_info.clone(),
+            coins(1, "uxion"),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::InsufficientUnallocatedFunds { denom } if denom == "uxion"
+        ));
+
+        // a sponsor can only be refunded their share of what remains
+        let err = refund_sponsor(
+            deps.as_mut(),
+            env.clone(),
+            admin_info.clone(),
+            sponsor_b.to_string(),
+            coins(76, "uxion"),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::RefundExceedsDeposit { .. }));
+        // a denom can't be refunded twice against the same balance
+        let err = refund_sponsor(
+            deps.as_mut(),
+            env.clone(),
+            admin_info.clone(),
+            sponsor_b.to_string(),
+            vec![Coin::new(40u128, "uxion"), Coin::new(40u128, "uxion")],
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::DuplicateRefundDenom { denom } if denom == "uxion"
+        ));
+        let res = refund_sponsor(
+            deps.as_mut(),
+            env.clone(),
+            admin_info.clone(),
+            sponsor_b.to_string(),
+            coins(0, "uxion"),
+        )
+        .unwrap();
+        assert!(res.messages.is_empty());
+        let res = refund_sponsor(
+            deps.as_mut(),
+            env.clone(),
+            admin_info.clone(),
+            sponsor_b.to_string(),
+            coins(30, "uxion"),
+        )
+        .unwrap();
+        assert_eq!(
+            refunds(&res),
+            vec![(sponsor_b.to_string(), coins(30, "uxion"))]
+        );
+        // 30 uxion is worth 40 of the deposit before fees
+        assert_eq!(
+            SPONSOR_DEPOSITS
+                .load(&deps.storage, ("uxion", &sponsor_b))
+                .unwrap(),
+            Uint128::new(60)
+        );
+        deps.querier
+            .bank
+            .update_balance(&env.contract.address, coins(270, "uxion"));
+
+        // the pro-rata refund is paid in batches, with the amount fixed by the first
+        let res = refund_sponsors_pro_rata(
+            deps.as_mut(),
+            env.clone(),
+            admin_info.clone(),
+            "uxion".to_string(),
+            Some(1),
+        )
+        .unwrap();
+        let mut paid = refunds(&res);
+        assert!(PRO_RATA_REFUNDS.has(&deps.storage, "uxion"));
+        let err = deposit(
+            deps.as_mut(),
+            message_info(&sponsor_a, &coins(100, "uxion")),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::RefundInProgress { .. }));
+
+        let res = refund_sponsors_pro_rata(
+            deps.as_mut(),
+            env.clone(),
+            admin_info.clone(),
+            "uxion".to_string(),
+            Some(1),
+        )
+        .unwrap();
+        paid.extend(refunds(&res));
+        paid.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut expected = vec![
+            (sponsor_a.to_string(), coins(225, "uxion")),
+            (sponsor_b.to_string(), coins(45, "uxion")),
+        ];
+        expected.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(paid, expected);
+        assert!(!PRO_RATA_REFUNDS.has(&deps.storage, "uxion"));
+        assert!(TOTAL_DEPOSITS.is_empty(&deps.storage));
+
+        withdraw_coins(deps.as_mut(), env, admin_info, coins(1, "uxion")).unwrap();
+    }
+
+    fn refunds(res: &Response) -> Vec<(String, Vec<Coin>)> {
+        res.messages
+            .iter()
+            .map(|msg| match &msg.msg {
+                CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+                    (to_address.clone(), amount.clone())
+                }
+                _ => panic!("unexpected message"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_roles() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let admin = deps.api.addr_make("admin");
+        let operator = deps.api.addr_make("operator");
+        let other = deps.api.addr_make("other");
+        ADMIN.save(deps.as_mut().storage, &admin).unwrap();
+        FEE_CONFIG
+            .save(deps.as_mut().storage, &fee_config(1))
+            .unwrap();
+        let admin_info = message_info(&admin, &[]);
+        let operator_info = message_info(&operator, &[]);
+
+        // only the admin proposes, and only the proposed member accepts
+        let err = propose_role(
+            deps.as_mut(),
+            message_info(&other, &[]),
+            Role::Operator,
+            operator.to_string(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized));
+        propose_role(
+            deps.as_mut(),
+            admin_info.clone(),
+            Role::Operator,
+            operator.to_string(),
+        )
+        .unwrap();
+        let err =
+            accept_role(deps.as_mut(), message_info(&other, &[]), Role::Operator).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized));
+        // accepting a different role than the one proposed
+        let err = accept_role(deps.as_mut(), operator_info.clone(), Role::Finance).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized));
+        accept_role(deps.as_mut(), operator_info.clone(), Role::Operator).unwrap();
+
+        // a role only grants its own permissions
+        assert_role(deps.as_ref(), &operator, Role::Operator).unwrap();
+        assert_role(deps.as_ref(), &admin, Role::Operator).unwrap();
+        revoke_allowance(
+            deps.as_mut(),
+            env.clone(),
+            operator_info.clone(),
+            other.clone(),
+        )
+        .unwrap();
+        let err = update_fee_config(
+            deps.as_mut(),
+            env.clone(),
+            operator_info.clone(),
+            fee_config(2),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized));
+
+        // a cancelled proposal can't be accepted
+        propose_role(
+            deps.as_mut(),
+            admin_info.clone(),
+            Role::Finance,
+            other.to_string(),
+        )
+        .unwrap();
+        assert!(cancel_proposed_role(
+            deps.as_mut(),
+            admin_info.clone(),
+            Role::Finance,
+            "not an address".to_string()
+        )
+        .is_err());
+        cancel_proposed_role(
+            deps.as_mut(),
+            admin_info.clone(),
+            Role::Finance,
+            other.to_string(),
+        )
+        .unwrap();
+        let err = accept_role(deps.as_mut(), message_info(&other, &[]), Role::Finance).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized));
+
+        let err = revoke_role(
+            deps.as_mut(),
+            operator_info.clone(),
+            Role::Operator,
+            operator.to_string(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized));
+        assert!(revoke_role(
+            deps.as_mut(),
+            admin_info.clone(),
+            Role::Operator,
+            "not an address".to_string()
+        )
+        .is_err());
+        revoke_role(
+            deps.as_mut(),
+            admin_info.clone(),
+            Role::Operator,
+            operator.to_string(),
+        )
+        .unwrap();
+        let err = revoke_allowance(deps.as_mut(), env, operator_info, other).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized));
+        let err = revoke_role(
+            deps.as_mut(),
+            admin_info,
+            Role::Operator,
+            operator.to_string(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::RoleNotFound { .. }));
+    }
+
+    #[test]
+    fn test_guardian_pause() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let admin = deps.api.addr_make("admin");
+        let guardian = deps.api.addr_make("guardian");
+        ADMIN.save(deps.as_mut().storage, &admin).unwrap();
+        ROLES
+            .save(
+                deps.as_mut().storage,
+                (Role::Guardian.as_str(), &guardian),
+                &true,
+            )
+            .unwrap();
+
+        let guardian_info = message_info(&guardian, &[]);
+        let unpause_at = env.block.time.plus_seconds(100);
+        pause(
+            deps.as_mut(),
+            env.clone(),
+            guardian_info.clone(),
+            Some(unpause_at),
+        )
+        .unwrap();
+        let err = deploy_fee_grant(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            guardian.clone(),
+            None,
+            None,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Paused));
+
+        // the guardian can extend the pause but not shorten or lift it
+        assert!(pause(
+            deps.as_mut(),
+            env.clone(),
+            guardian_info.clone(),
+            Some(unpause_at.minus_seconds(1))
+        )
+        .is_err());
+        assert!(unpause(deps.as_mut(), guardian_info).is_err());
+
+        // the pause lifts itself at the unpause time
+        env.block.time = unpause_at;
+        let err =
+            deploy_fee_grant(deps.as_mut(), env, admin.clone(), guardian, None, None).unwrap_err();
+        assert!(!matches!(err, ContractError::Paused));
+
+        unpause(deps.as_mut(), message_info(&admin, &[])).unwrap();
+    }
+
+    #[test]
+    fn test_validate_metadata() {
+        let deps = mock_dependencies();
+        let params = |metadata: &str| Params {
+            redirect_url: "https://example.com".to_string(),
+            icon_url: "https://example.com/icon.png".to_string(),
+            metadata: metadata.to_string(),
+        };
+
+        // free-form metadata is still accepted, even with a version of its own
+        validate_params(&deps.storage, &params(r#"{"name":"app"}"#)).unwrap();
+        validate_params(&deps.storage, &params(r#"{"name":"app","version":"2.1"}"#)).unwrap();
+        validate_params(
+            &deps.storage,
+            &params(r#"{"schema_version":1,"display_name":"App","terms_of_service_url":"https://example.com/tos"}"#),
+        )
+        .unwrap();
+
+        for invalid in [
+            r#"{"schema_version":2,"display_name":"App"}"#,
+            r#"{"schema_version":1,"display_name":" "}"#,
+            r#"{"schema_version":1,"display_name":"App","privacy_policy_url":"not a url"}"#,
+            r#"{"schema_version":1,"display_name":"App","msg_descriptions":{"/cosmos.bank.v1beta1.MsgSend":"Send tokens"}}"#,
+        ] {
+            assert!(validate_params(&deps.storage, &params(invalid)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_remove_described_grant_config() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let admin = deps.api.addr_make("admin");
+        let info = message_info(&admin, &[]);
+        ADMIN.save(deps.as_mut().storage, &admin).unwrap();
+        let send = "/cosmos.bank.v1beta1.MsgSend".to_string();
+        let delegate = "/cosmos.staking.v1beta1.MsgDelegate".to_string();
+        for msg_type_url in [&send, &delegate] {
+            GRANT_CONFIGS
+                .save(
+                    deps.as_mut().storage,
+                    msg_type_url.clone(),
+                    &grant_config(false),
+                )
+                .unwrap();
+        }
+        let params = Params {
+            redirect_url: "https://example.com".to_string(),
+            icon_url: "https://example.com/icon.png".to_string(),
+            metadata: format!(
+                r#"{{"schema_version":1,"display_name":"App","msg_descriptions":{{"{send}":"Send tokens","{delegate}":"Stake tokens"}}}}"#
+            ),
+        };
+        validate_params(&deps.storage, &params).unwrap();
+        PARAMS.save(deps.as_mut().storage, &params).unwrap();
+
+        // removing a grant config drops its description, leaving the params valid
+        remove_grant_config(deps.as_mut(), env, info, send.clone()).unwrap();
+        let params = PARAMS.load(&deps.storage).unwrap();
+        validate_params(&deps.storage, &params).unwrap();
+        let metadata = parse_metadata(&params.metadata).unwrap().unwrap();
+        assert_eq!(
+            metadata.msg_descriptions.keys().collect::<Vec<_>>(),
+            vec![&delegate]
+        );
+        assert_eq!(metadata.display_name, "App");
+    }
+}
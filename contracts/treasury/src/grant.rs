@@ -1,4 +1,5 @@
 pub mod allowance;
+pub mod authorization;
 
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::Binary;
@@ -17,6 +18,26 @@ pub struct FeeConfig {
     pub expiration: Option<u32>,
 }
 
+/// The state of a grantee's authz grant for a grant config.
+#[cw_serde]
+pub enum GrantStatus {
+    /// Present and within the configured authorization
+    Present,
+    MissingOptional,
+    Missing,
+    /// Present but broader than the configured authorization
+    Mismatched,
+}
+
+/// A named alternative to the default fee config.
+#[cw_serde]
+pub struct FeeTier {
+    pub fee_config: FeeConfig,
+    /// Optional grant configs which, when all granted, select this tier for
+    /// grantees that aren't assigned one
+    pub optional_grants: Vec<String>,
+}
+
 #[cw_serde]
 pub struct Any {
     pub type_url: String,
@@ -1,6 +1,7 @@
 use crate::error::ContractError::{AllowanceUnset, InvalidAllowanceType};
 use crate::error::ContractResult;
 use crate::grant::Any;
+use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
 use cosmos_sdk_proto::cosmos::feegrant::v1beta1::{
     AllowedMsgAllowance, BasicAllowance, PeriodicAllowance,
 };
@@ -8,7 +9,9 @@ use cosmos_sdk_proto::prost::Message;
 use cosmos_sdk_proto::traits::MessageExt;
 use cosmos_sdk_proto::xion::v1::{AuthzAllowance, ContractsAllowance, MultiAnyAllowance};
 use cosmos_sdk_proto::Timestamp;
-use cosmwasm_std::Addr;
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Coin, Coins, Uint128};
+use std::str::FromStr;
 
 #[allow(dead_code)]
 pub fn format_allowance(
@@ -125,3 +128,272 @@ pub fn format_allowance(
 
     Ok(formatted_allowance)
 }
+
+fn coins(coins: Vec<ProtoCoin>) -> ContractResult<Vec<Coin>> {
+    coins
+        .into_iter()
+        .map(|coin| Ok(Coin::new(Uint128::from_str(&coin.amount)?, coin.denom)))
+        .collect()
+}
+
+// an empty spend limit is unbounded
+fn bounded(coins: Vec<Coin>) -> Option<Vec<Coin>> {
+    (!coins.is_empty()).then_some(coins)
+}
+
+fn timestamp(timestamp: Timestamp) -> cosmwasm_std::Timestamp {
+    cosmwasm_std::Timestamp::from_seconds(timestamp.seconds as u64)
+        .plus_nanos(timestamp.nanos as u64)
+}
+
+/// What remains of a deployed allowance, across all allowance types.
+#[cw_serde]
+pub struct AllowanceReport {
+    pub type_url: String,
+    /// The remaining spend limit, `None` if unbounded
+    pub spend_limit: Option<Vec<Coin>>,
+    /// What can still be spent in the current period of a periodic allowance
+    pub period_can_spend: Option<Vec<Coin>>,
+    pub period_reset: Option<cosmwasm_std::Timestamp>,
+    pub expiration: Option<cosmwasm_std::Timestamp>,
+}
+
+/// Normalises an allowance deployed on chain. For a `MultiAnyAllowance` the
+/// spend limits are summed, the earliest period reset is reported and it
+/// expires with the last of its allowances.
+pub fn allowance_report(allowance_any: &Any) -> ContractResult<AllowanceReport> {
+    let (period_can_spend, period_reset, expiration) = match allowance_any.type_url.as_str() {
+        "/cosmos.feegrant.v1beta1.BasicAllowance" => {
+            let allowance = BasicAllowance::decode(allowance_any.value.as_slice())?;
+            (None, None, allowance.expiration.map(timestamp))
+        }
+        "/cosmos.feegrant.v1beta1.PeriodicAllowance" => {
+            let allowance = PeriodicAllowance::decode(allowance_any.value.as_slice())?;
+            let basic = allowance.basic.ok_or(AllowanceUnset)?;
+            (
+                Some(coins(allowance.period_can_spend)?),
+                allowance.period_reset.map(timestamp),
+                basic.expiration.map(timestamp),
+            )
+        }
+        "/cosmos.feegrant.v1beta1.AllowedMsgAllowance" => {
+            let allowance = AllowedMsgAllowance::decode(allowance_any.value.as_slice())?;
+            let inner = allowance_report(&allowance.allowance.ok_or(AllowanceUnset)?.into())?;
+            (inner.period_can_spend, inner.period_reset, inner.expiration)
+        }
+        "/xion.v1.AuthzAllowance" => {
+            let allowance = AuthzAllowance::decode(allowance_any.value.as_slice())?;
+            let inner = allowance_report(&allowance.allowance.ok_or(AllowanceUnset)?.into())?;
+            (inner.period_can_spend, inner.period_reset, inner.expiration)
+        }
+        "/xion.v1.ContractsAllowance" => {
+            let allowance = ContractsAllowance::decode(allowance_any.value.as_slice())?;
+            let inner = allowance_report(&allowance.allowance.ok_or(AllowanceUnset)?.into())?;
+            (inner.period_can_spend, inner.period_reset, inner.expiration)
+        }
+        "/xion.v1.MultiAnyAllowance" => {
+            let allowance = MultiAnyAllowance::decode(allowance_any.value.as_slice())?;
+            let mut period_can_spend: Option<Coins> = None;
+            let mut period_reset = None;
+            let mut expiration = Some(None);
+            for inner_allowance in allowance.allowances {
+                let inner = allowance_report(&inner_allowance.into())?;
+                if let Some(can_spend) = inner.period_can_spend {
+                    let total = period_can_spend.get_or_insert_with(Coins::default);
+                    for coin in can_spend {
+                        total.add(coin)?;
+                    }
+                }
+                period_reset = match (period_reset, inner.period_reset) {
+                    (Some(a), Some(b)) => Some(std::cmp::min(a, b)),
+                    (a, b) => a.or(b),
+                };
+                // an allowance without expiration keeps the whole one alive
+                expiration = match (expiration, inner.expiration) {
+                    (Some(a), Some(b)) => Some(Some(a.map_or(b, |a| std::cmp::max(a, b)))),
+                    _ => None,
+                };
+            }
+            (
+                period_can_spend.map(Coins::into_vec),
+                period_reset,
+                expiration.flatten(),
+            )
+        }
+        _ => {
+            return Err(InvalidAllowanceType {
+                msg_type_url: allowance_any.type_url.clone(),
+            })
+        }
+    };
+
+    Ok(AllowanceReport {
+        type_url: allowance_any.type_url.clone(),
+        spend_limit: spend_limit(allowance_any)?,
+        period_can_spend,
+        period_reset,
+        expiration,
+    })
+}
+
+/// The most the grantee can spend from an allowance, or `None` if it is
+/// unbounded. Allowances in a `MultiAnyAllowance` are counted in full.
+pub fn spend_limit(allowance_any: &Any) -> ContractResult<Option<Vec<Coin>>> {
+    match allowance_any.type_url.as_str() {
+        "/cosmos.feegrant.v1beta1.BasicAllowance" => {
+            let allowance = BasicAllowance::decode(allowance_any.value.as_slice())?;
+            Ok(bounded(coins(allowance.spend_limit)?))
+        }
+        "/cosmos.feegrant.v1beta1.PeriodicAllowance" => {
+            let allowance = PeriodicAllowance::decode(allowance_any.value.as_slice())?;
+            Ok(bounded(coins(
+                allowance.basic.ok_or(AllowanceUnset)?.spend_limit,
+            )?))
+        }
+        "/cosmos.feegrant.v1beta1.AllowedMsgAllowance" => {
+            let allowance = AllowedMsgAllowance::decode(allowance_any.value.as_slice())?;
+            spend_limit(&allowance.allowance.ok_or(AllowanceUnset)?.into())
+        }
+        "/xion.v1.AuthzAllowance" => {
+            let allowance = AuthzAllowance::decode(allowance_any.value.as_slice())?;
+            spend_limit(&allowance.allowance.ok_or(AllowanceUnset)?.into())
+        }
+        "/xion.v1.ContractsAllowance" => {
+            let allowance = ContractsAllowance::decode(allowance_any.value.as_slice())?;
+            spend_limit(&allowance.allowance.ok_or(AllowanceUnset)?.into())
+        }
+        "/xion.v1.MultiAnyAllowance" => {
+            let allowance = MultiAnyAllowance::decode(allowance_any.value.as_slice())?;
+            let mut total = Coins::default();
+            for inner_allowance in allowance.allowances {
+                match spend_limit(&inner_allowance.into())? {
+                    None => return Ok(None),
+                    Some(coins) => {
+                        for coin in coins {
+                            total.add(coin)?;
+                        }
+                    }
+                }
+            }
+            Ok(Some(total.into_vec()))
+        }
+        _ => Err(InvalidAllowanceType {
+            msg_type_url: allowance_any.type_url.clone(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grant::allowance::{allowance_report, spend_limit};
+    use crate::grant::Any;
+    use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+    use cosmos_sdk_proto::cosmos::feegrant::v1beta1::{BasicAllowance, PeriodicAllowance};
+    use cosmos_sdk_proto::traits::MessageExt;
+    use cosmos_sdk_proto::xion::v1::MultiAnyAllowance;
+    use cosmwasm_std::{coin, Timestamp};
+
+    fn basic_allowance(amounts: &[u128]) -> Any {
+        Any {
+            type_url: "/cosmos.feegrant.v1beta1.BasicAllowance".to_string(),
+            value: BasicAllowance {
+                spend_limit: amounts
+                    .iter()
+                    .map(|amount| ProtoCoin {
+                        denom: "uxion".to_string(),
+                        amount: amount.to_string(),
+                    })
+                    .collect(),
+                expiration: None,
+            }
+            .to_bytes()
+            .unwrap()
+            .into(),
+        }
+    }
+
+    #[test]
+    fn test_spend_limit() {
+        assert_eq!(
+            spend_limit(&basic_allowance(&[100])).unwrap(),
+            Some(vec![coin(100, "uxion")])
+        );
+        assert_eq!(spend_limit(&basic_allowance(&[])).unwrap(), None);
+
+        let multi_any = |allowances: Vec<Any>| Any {
+            type_url: "/xion.v1.MultiAnyAllowance".to_string(),
+            value: MultiAnyAllowance {
+                allowances: allowances.into_iter().map(Into::into).collect(),
+            }
+            .to_bytes()
+            .unwrap()
+            .into(),
+        };
+        assert_eq!(
+            spend_limit(&multi_any(vec![
+                basic_allowance(&[100]),
+                basic_allowance(&[50])
+            ]))
+            .unwrap(),
+            Some(vec![coin(150, "uxion")])
+        );
+        assert_eq!(
+            spend_limit(&multi_any(vec![
+                basic_allowance(&[100]),
+                basic_allowance(&[])
+            ]))
+            .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_allowance_report() {
+        let periodic = Any {
+            type_url: "/cosmos.feegrant.v1beta1.PeriodicAllowance".to_string(),
+            value: PeriodicAllowance {
+                basic: Some(BasicAllowance {
+                    spend_limit: vec![],
+                    expiration: Some(cosmos_sdk_proto::Timestamp {
+                        seconds: 200,
+                        nanos: 0,
+                    }),
+                }),
+                period: None,
+                period_spend_limit: vec![],
+                period_can_spend: vec![ProtoCoin {
+                    denom: "uxion".to_string(),
+                    amount: "10".to_string(),
+                }],
+                period_reset: Some(cosmos_sdk_proto::Timestamp {
+                    seconds: 100,
+                    nanos: 0,
+                }),
+            }
+            .to_bytes()
+            .unwrap()
+            .into(),
+        };
+
+        let report = allowance_report(&periodic).unwrap();
+        assert_eq!(report.spend_limit, None);
+        assert_eq!(report.period_can_spend, Some(vec![coin(10, "uxion")]));
+        assert_eq!(report.period_reset, Some(Timestamp::from_seconds(100)));
+        assert_eq!(report.expiration, Some(Timestamp::from_seconds(200)));
+
+        // the basic allowance never expires, so neither does the combination
+        let multi_any = Any {
+            type_url: "/xion.v1.MultiAnyAllowance".to_string(),
+            value: MultiAnyAllowance {
+                allowances: vec![periodic.into(), basic_allowance(&[100]).into()],
+            }
+            .to_bytes()
+            .unwrap()
+            .into(),
+        };
+        let report = allowance_report(&multi_any).unwrap();
+        assert_eq!(report.spend_limit, None);
+        assert_eq!(report.period_reset, Some(Timestamp::from_seconds(100)));
+        assert_eq!(report.expiration, None);
+    }
+}
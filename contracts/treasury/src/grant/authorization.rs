@@ -0,0 +1,552 @@
+use crate::error::ContractResult;
+use crate::grant::Any;
+use cosmos_sdk_proto::cosmos::authz::v1beta1::{GenericAuthorization, Grant, MsgGrant};
+use cosmos_sdk_proto::cosmos::bank::v1beta1::SendAuthorization;
+use cosmos_sdk_proto::cosmos::base::v1beta1::Coin;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::stake_authorization::Policy;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::StakeAuthorization;
+use cosmos_sdk_proto::cosmwasm::wasm::v1::{
+    AcceptedMessageKeysFilter, AcceptedMessagesFilter, CombinedLimit,
+    ContractExecutionAuthorization, ContractGrant, MaxCallsLimit, MaxFundsLimit,
+};
+use cosmos_sdk_proto::prost::Message;
+use cosmos_sdk_proto::traits::MessageExt;
+use cosmwasm_std::{Addr, Timestamp, Uint128};
+use std::str::FromStr;
+
+/// Whether the `granted` authorization allows no more than the `configured`
+/// one. Authorizations of unknown types must match exactly.
+pub fn is_within(configured: &Any, granted: &Any) -> ContractResult<bool> {
+    if configured.eq(granted) {
+        return Ok(true);
+    }
+    if configured.type_url != granted.type_url {
+        return Ok(false);
+    }
+
+    match configured.type_url.as_str() {
+        "/cosmos.authz.v1beta1.GenericAuthorization" => {
+            let configured = GenericAuthorization::decode(configured.value.as_slice())?;
+            let granted = GenericAuthorization::decode(granted.value.as_slice())?;
+            Ok(configured.msg == granted.msg)
+        }
+        "/cosmos.bank.v1beta1.SendAuthorization" => {
+            let configured = SendAuthorization::decode(configured.value.as_slice())?;
+            let granted = SendAuthorization::decode(granted.value.as_slice())?;
+            Ok(coins_within(&configured.spend_limit, &granted.spend_limit)?
+                && list_within(&configured.allow_list, &granted.allow_list))
+        }
+        "/cosmos.staking.v1beta1.StakeAuthorization" => {
+            let configured = StakeAuthorization::decode(configured.value.as_slice())?;
+            let granted = StakeAuthorization::decode(granted.value.as_slice())?;
+            if configured.authorization_type != granted.authorization_type {
+                return Ok(false);
+            }
+            let max_tokens_within = match (configured.max_tokens, granted.max_tokens) {
+                (None, _) => true,
+                (Some(_), None) => false,
+                (Some(configured), Some(granted)) => coins_within(&[configured], &[granted])?,
+            };
+            Ok(max_tokens_within && validators_within(&configured.validators, &granted.validators))
+        }
+        "/cosmwasm.wasm.v1.ContractExecutionAuthorization" => {
+            let configured = ContractExecutionAuthorization::decode(configured.value.as_slice())?;
+            let granted = ContractExecutionAuthorization::decode(granted.value.as_slice())?;
+            for granted_grant in granted.grants.iter() {
+                let mut covered = false;
+                for configured_grant in configured
+                    .grants
+                    .iter()
+                    .filter(|g| g.contract == granted_grant.contract)
+                {
+                    if contract_grant_within(configured_grant, granted_grant)? {
+                        covered = true;
+                        break;
+                    }
+                }
+                if !covered {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// A `MsgGrant` from the granter to the grantee for the authorization.
+pub fn msg_grant(
+    granter: &Addr,
+    grantee: &Addr,
+    authorization: &Any,
+    expiration: Option<Timestamp>,
+) -> ContractResult<Any> {
+    let msg = MsgGrant {
+        granter: granter.to_string(),
+        grantee: grantee.to_string(),
+        grant: Some(Grant {
+            authorization: Some(authorization.clone().into()),
+            expiration: expiration.map(|expiration| cosmos_sdk_proto::Timestamp {
+                seconds: expiration.seconds() as i64,
+                nanos: expiration.subsec_nanos() as i32,
+            }),
+        }),
+    };
+
+    Ok(Any {
+        type_url: "/cosmos.authz.v1beta1.MsgGrant".to_string(),
+        value: msg.to_bytes()?.into(),
+    })
+}
+
+fn amount(coin: &Coin) -> ContractResult<Uint128> {
+    Ok(Uint128::from_str(&coin.amount)?)
+}
+
+/// Every granted coin has a denom in the configured coins, with an amount no
+/// larger than the configured one.
+fn coins_within(configured: &[Coin], granted: &[Coin]) -> ContractResult<bool> {
+    if granted.is_empty() && !configured.is_empty() {
+        return Ok(false);
+    }
+    for coin in granted {
+        match configured.iter().find(|c| c.denom == coin.denom) {
+            None => return Ok(false),
+            Some(limit) => {
+                if amount(coin)? > amount(limit)? {
+                    return Ok(false);
+                }
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// An empty list allows anything, so a granted list is within the configured
+/// one when the configured list is empty or contains all granted entries.
+fn list_within(configured: &[String], granted: &[String]) -> bool {
+    configured.is_empty() || (!granted.is_empty() && granted.iter().all(|g| configured.contains(g)))
+}
+
+fn validators_within(configured: &Option<Policy>, granted: &Option<Policy>) -> bool {
+    match (configured, granted) {
+        (None, _) => true,
+        (Some(_), None) => false,
+        (Some(Policy::AllowList(configured)), Some(Policy::AllowList(granted))) => {
+            !granted.address.is_empty()
+                && granted
+                    .address
+                    .iter()
+                    .all(|a| configured.address.contains(a))
+        }
+        (Some(Policy::AllowList(_)), Some(Policy::DenyList(_))) => false,
+        (Some(Policy::DenyList(configured)), Some(Policy::DenyList(granted))) => configured
+            .address
+            .iter()
+            .all(|a| granted.address.contains(a)),
+        // an empty allow list permits every validator, the denied ones too
+        (Some(Policy::DenyList(configured)), Some(Policy::AllowList(granted))) => {
+            !granted.address.is_empty()
+                && granted
+                    .address
+                    .iter()
+                    .all(|a| !configured.address.contains(a))
+        }
+    }
+}
+
+fn contract_grant_within(
+    configured: &ContractGrant,
+    granted: &ContractGrant,
+) -> ContractResult<bool> {
+    // wasmd requires both a limit and a filter, anything else must match exactly
+    let (
+        Some(configured_limit),
+        Some(granted_limit),
+        Some(configured_filter),
+        Some(granted_filter),
+    ) = (
+        &configured.limit,
+        &granted.limit,
+        &configured.filter,
+        &granted.filter,
+    )
+    else {
+        return Ok(configured == granted);
+    };
+
+    Ok(limit_within(
+        &configured_limit.clone().into(),
+        &granted_limit.clone().into(),
+    )? && filter_within(
+        &configured_filter.clone().into(),
+        &granted_filter.clone().into(),
+    )?)
+}
+
+fn limit_within(configured: &Any, granted: &Any) -> ContractResult<bool> {
+    if configured.eq(granted) {
+        return Ok(true);
+    }
+
+    match (configured.type_url.as_str(), granted.type_url.as_str()) {
+        ("/cosmwasm.wasm.v1.MaxCallsLimit", "/cosmwasm.wasm.v1.MaxCallsLimit") => {
+            let configured = MaxCallsLimit::decode(configured.value.as_slice())?;
+            let granted = MaxCallsLimit::decode(granted.value.as_slice())?;
+            Ok(granted.remaining <= configured.remaining)
+        }
+        ("/cosmwasm.wasm.v1.MaxFundsLimit", "/cosmwasm.wasm.v1.MaxFundsLimit") => {
+            let configured = MaxFundsLimit::decode(configured.value.as_slice())?;
+            let granted = MaxFundsLimit::decode(granted.value.as_slice())?;
+            coins_within(&configured.amounts, &granted.amounts)
+        }
+        ("/cosmwasm.wasm.v1.CombinedLimit", "/cosmwasm.wasm.v1.CombinedLimit") => {
+            let configured = CombinedLimit::decode(configured.value.as_slice())?;
+            let granted = CombinedLimit::decode(granted.value.as_slice())?;
+            Ok(granted.calls_remaining <= configured.calls_remaining
+                && coins_within(&configured.amounts, &granted.amounts)?)
+        }
+        _ => Ok(false),
+    }
+}
+
+fn filter_within(configured: &Any, granted: &Any) -> ContractResult<bool> {
+    if configured.eq(granted) {
+        return Ok(true);
+    }
+
+    match (configured.type_url.as_str(), granted.type_url.as_str()) {
+        ("/cosmwasm.wasm.v1.AllowAllMessagesFilter", _) => Ok(true),
+        (
+            "/cosmwasm.wasm.v1.AcceptedMessageKeysFilter",
+            "/cosmwasm.wasm.v1.AcceptedMessageKeysFilter",
+        ) => {
+            let configured = AcceptedMessageKeysFilter::decode(configured.value.as_slice())?;
+            let granted = AcceptedMessageKeysFilter::decode(granted.value.as_slice())?;
+            Ok(
+                !granted.keys.is_empty()
+                    && granted.keys.iter().all(|k| configured.keys.contains(k)),
+            )
+        }
+        (
+            "/cosmwasm.wasm.v1.AcceptedMessagesFilter",
+            "/cosmwasm.wasm.v1.AcceptedMessagesFilter",
+        ) => {
+            let configured = AcceptedMessagesFilter::decode(configured.value.as_slice())?;
+            let granted = AcceptedMessagesFilter::decode(granted.value.as_slice())?;
+            Ok(!granted.messages.is_empty()
+                && granted
+                    .messages
+                    .iter()
+                    .all(|m| configured.messages.contains(m)))
+        }
+        _ => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grant::authorization::is_within;
+    use crate::grant::Any;
+    use cosmos_sdk_proto::cosmos::bank::v1beta1::SendAuthorization;
+    use cosmos_sdk_proto::cosmos::base::v1beta1::Coin;
+    use cosmos_sdk_proto::cosmos::staking::v1beta1::stake_authorization::{Policy, Validators};
+    use cosmos_sdk_proto::cosmos::staking::v1beta1::StakeAuthorization;
+    use cosmos_sdk_proto::cosmwasm::wasm::v1::{
+        AcceptedMessageKeysFilter, AllowAllMessagesFilter, CombinedLimit,
+        ContractExecutionAuthorization, ContractGrant, MaxCallsLimit, MaxFundsLimit,
+    };
+    use cosmos_sdk_proto::traits::{Message, MessageExt};
+
+    fn send_authorization(amount: &str, allow_list: Vec<&str>) -> Any {
+        Any {
+            type_url: "/cosmos.bank.v1beta1.SendAuthorization".to_string(),
+            value: SendAuthorization {
+                spend_limit: vec![Coin {
+                    denom: "uxion".to_string(),
+                    amount: amount.to_string(),
+                }],
+                allow_list: allow_list.into_iter().map(String::from).collect(),
+            }
+            .to_bytes()
+            .unwrap()
+            .into(),
+        }
+    }
+
+    #[test]
+    fn test_send_authorization() {
+        let configured = send_authorization("100", vec![]);
+
+        assert!(is_within(&configured, &send_authorization("100", vec![])).unwrap());
+        assert!(is_within(&configured, &send_authorization("50", vec!["xion1a"])).unwrap());
+        assert!(!is_within(&configured, &send_authorization("150", vec![])).unwrap());
+
+        let configured = send_authorization("100", vec!["xion1a", "xion1b"]);
+        assert!(is_within(&configured, &send_authorization("100", vec!["xion1b"])).unwrap());
+        assert!(!is_within(&configured, &send_authorization("100", vec![])).unwrap());
+        assert!(!is_within(&configured, &send_authorization("100", vec!["xion1c"])).unwrap());
+    }
+
+    #[test]
+    fn test_stake_authorization() {
+        let stake_authorization = |max: Option<&str>, validators: Policy| Any {
+            type_url: "/cosmos.staking.v1beta1.StakeAuthorization".to_string(),
+            value: StakeAuthorization {
+                max_tokens: max.map(|amount| Coin {
+                    denom: "uxion".to_string(),
+                    amount: amount.to_string(),
+                }),
+                authorization_type: 1,
+                validators: Some(validators),
+            }
+            .to_bytes()
+            .unwrap()
+            .into(),
+        };
+        let list = |addresses: Vec<&str>| Validators {
+            address: addresses.into_iter().map(String::from).collect(),
+        };
+
+        // an allow list is narrower when it only drops validators
+        let configured =
+            stake_authorization(Some("100"), Policy::AllowList(list(vec!["val1", "val2"])));
+        assert!(is_within(
+            &configured,
+            &stake_authorization(Some("100"), Policy::AllowList(list(vec!["val1"])))
+        )
+        .unwrap());
+        assert!(!is_within(
+            &configured,
+            &stake_authorization(
+                Some("100"),
+                Policy::AllowList(list(vec!["val1", "val2", "val3"]))
+            )
+        )
+        .unwrap());
+        assert!(!is_within(
+            &configured,
+            &stake_authorization(Some("100"), Policy::AllowList(list(vec![])))
+        )
+        .unwrap());
+        assert!(!is_within(
+            &configured,
+            &stake_authorization(Some("150"), Policy::AllowList(list(vec!["val1"])))
+        )
+        .unwrap());
+        assert!(!is_within(
+            &configured,
+            &stake_authorization(None, Policy::AllowList(list(vec!["val1"])))
+        )
+        .unwrap());
+        // a deny list allows every validator it doesn't name
+        assert!(!is_within(
+            &configured,
+            &stake_authorization(Some("100"), Policy::DenyList(list(vec!["val3"])))
+        )
+        .unwrap());
+
+        // a deny list is narrower when it only adds validators
+        let configured = stake_authorization(None, Policy::DenyList(list(vec!["val1"])));
+        assert!(is_within(
+            &configured,
+            &stake_authorization(None, Policy::DenyList(list(vec!["val1", "val2"])))
+        )
+        .unwrap());
+        assert!(is_within(
+            &configured,
+            &stake_authorization(Some("10"), Policy::AllowList(list(vec!["val2"])))
+        )
+        .unwrap());
+        assert!(!is_within(
+            &configured,
+            &stake_authorization(None, Policy::AllowList(list(vec!["val1"])))
+        )
+        .unwrap());
+        assert!(!is_within(
+            &configured,
+            &stake_authorization(None, Policy::AllowList(list(vec![])))
+        )
+        .unwrap());
+        assert!(!is_within(
+            &configured,
+            &stake_authorization(None, Policy::DenyList(list(vec![])))
+        )
+        .unwrap());
+    }
+
+    fn any<T: Message>(type_url: &str, msg: T) -> cosmos_sdk_proto::Any {
+        cosmos_sdk_proto::Any {
+            type_url: type_url.to_string(),
+            value: msg.encode_to_vec(),
+        }
+    }
+
+    fn calls_limit(remaining: u64) -> cosmos_sdk_proto::Any {
+        any(
+            "/cosmwasm.wasm.v1.MaxCallsLimit",
+            MaxCallsLimit { remaining },
+        )
+    }
+
+    fn funds_limit(amount: &str) -> cosmos_sdk_proto::Any {
+        any(
+            "/cosmwasm.wasm.v1.MaxFundsLimit",
+            MaxFundsLimit {
+                amounts: vec![Coin {
+                    denom: "uxion".to_string(),
+                    amount: amount.to_string(),
+                }],
+            },
+        )
+    }
+
+    fn keys_filter(keys: Vec<&str>) -> cosmos_sdk_proto::Any {
+        any(
+            "/cosmwasm.wasm.v1.AcceptedMessageKeysFilter",
+            AcceptedMessageKeysFilter {
+                keys: keys.into_iter().map(String::from).collect(),
+            },
+        )
+    }
+
+    fn contract_execution_authorization(
+        grants: Vec<(
+            &str,
+            Option<cosmos_sdk_proto::Any>,
+            Option<cosmos_sdk_proto::Any>,
+        )>,
+    ) -> Any {
+        Any {
+            type_url: "/cosmwasm.wasm.v1.ContractExecutionAuthorization".to_string(),
+            value: ContractExecutionAuthorization {
+                grants: grants
+                    .into_iter()
+                    .map(|(contract, limit, filter)| ContractGrant {
+                        contract: contract.to_string(),
+                        limit,
+                        filter,
+                    })
+                    .collect(),
+            }
+            .to_bytes()
+            .unwrap()
+            .into(),
+        }
+    }
+
+    #[test]
+    fn test_contract_execution_limits() {
+        let within = |configured, granted| {
+            is_within(
+                &contract_execution_authorization(vec![(
+                    "xion1c",
+                    Some(configured),
+                    Some(keys_filter(vec!["a"])),
+                )]),
+                &contract_execution_authorization(vec![(
+                    "xion1c",
+                    Some(granted),
+                    Some(keys_filter(vec!["a"])),
+                )]),
+            )
+            .unwrap()
+        };
+
+        assert!(within(calls_limit(10), calls_limit(5)));
+        assert!(!within(calls_limit(10), calls_limit(11)));
+        assert!(within(funds_limit("100"), funds_limit("100")));
+        assert!(!within(funds_limit("100"), funds_limit("101")));
+        let combined = |calls_remaining, amount: &str| {
+            any(
+                "/cosmwasm.wasm.v1.CombinedLimit",
+                CombinedLimit {
+                    calls_remaining,
+                    amounts: vec![Coin {
+                        denom: "uxion".to_string(),
+                        amount: amount.to_string(),
+                    }],
+                },
+            )
+        };
+        assert!(within(combined(10, "100"), combined(10, "50")));
+        assert!(!within(combined(10, "100"), combined(11, "50")));
+        assert!(!within(combined(10, "100"), combined(10, "150")));
+        // a different kind of limit is never within
+        assert!(!within(calls_limit(10), funds_limit("1")));
+    }
+
+    #[test]
+    fn test_contract_execution_filters() {
+        let within = |configured, granted| {
+            is_within(
+                &contract_execution_authorization(vec![(
+                    "xion1c",
+                    Some(calls_limit(10)),
+                    Some(configured),
+                )]),
+                &contract_execution_authorization(vec![(
+                    "xion1c",
+                    Some(calls_limit(10)),
+                    Some(granted),
+                )]),
+            )
+            .unwrap()
+        };
+        let allow_all = || {
+            any(
+                "/cosmwasm.wasm.v1.AllowAllMessagesFilter",
+                AllowAllMessagesFilter {},
+            )
+        };
+
+        assert!(within(keys_filter(vec!["a", "b"]), keys_filter(vec!["a"])));
+        assert!(!within(keys_filter(vec!["a"]), keys_filter(vec!["a", "b"])));
+        assert!(!within(keys_filter(vec!["a"]), keys_filter(vec![])));
+        assert!(within(allow_all(), keys_filter(vec!["c"])));
+        assert!(!within(keys_filter(vec!["a"]), allow_all()));
+    }
+
+    #[test]
+    fn test_contract_execution_grants() {
+        let configured = contract_execution_authorization(vec![
+            (
+                "xion1a",
+                Some(calls_limit(10)),
+                Some(keys_filter(vec!["a"])),
+            ),
+            ("xion1b", None, None),
+        ]);
+
+        // each granted contract must be covered by a configured grant
+        assert!(is_within(
+            &configured,
+            &contract_execution_authorization(vec![(
+                "xion1a",
+                Some(calls_limit(1)),
+                Some(keys_filter(vec!["a"]))
+            )])
+        )
+        .unwrap());
+        assert!(!is_within(
+            &configured,
+            &contract_execution_authorization(vec![(
+                "xion1c",
+                Some(calls_limit(1)),
+                Some(keys_filter(vec!["a"]))
+            )])
+        )
+        .unwrap());
+        // without both a limit and a filter, the grant must match exactly
+        assert!(is_within(
+            &configured,
+            &contract_execution_authorization(vec![("xion1b", None, None)])
+        )
+        .unwrap());
+        assert!(!is_within(
+            &configured,
+            &contract_execution_authorization(vec![("xion1b", Some(calls_limit(1)), None)])
+        )
+        .unwrap());
+    }
+}
@@ -1,14 +1,17 @@
 extern crate core;
 
-#[cfg(not(feature = "library"))]
 pub mod contract;
 mod error;
 mod execute;
+mod migrations;
 pub mod msg;
 mod state;
 
-mod grant;
+pub mod grant;
 mod query;
+mod voucher;
+
+pub use state::{GrantLimits, GranteeRecord, Params, Role, SpendBudget};
 
 pub const CONTRACT_NAME: &str = "treasury";
 pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -1,7 +1,12 @@
-use crate::grant::{FeeConfig, GrantConfig};
-use crate::state::Params;
+use crate::grant::allowance::AllowanceReport;
+use crate::grant::{Any, FeeConfig, FeeTier, GrantConfig, GrantStatus};
+use crate::state::{
+    BudgetUsage, GrantLimits, GranteeHistory, GranteeRecord, Params, ParamsMetadata, PauseState,
+    PendingConfigChange, Role, SpendBudget,
+};
+use crate::voucher::{SponsorKey, Voucher};
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Binary, Coin};
+use cosmwasm_std::{Addr, Binary, Coin, Timestamp};
 
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -19,6 +24,21 @@ pub enum ExecuteMsg {
     },
     AcceptAdmin {},
     CancelProposedAdmin {},
+    ProposeRole {
+        role: Role,
+        member: Addr,
+    },
+    AcceptRole {
+        role: Role,
+    },
+    CancelProposedRole {
+        role: Role,
+        member: Addr,
+    },
+    RevokeRole {
+        role: Role,
+        member: Addr,
+    },
     UpdateGrantConfig {
         msg_type_url: String,
         grant_config: GrantConfig,
@@ -29,25 +49,124 @@ pub enum ExecuteMsg {
     UpdateFeeConfig {
         fee_config: FeeConfig,
     },
+    /// Set the delay before config changes take effect: grant configs, the
+    /// fee config and tiers, the sponsor key, grant limits and the spend
+    /// budget, up to 30 days. The change itself waits for the current delay
+    UpdateConfigDelay {
+        seconds: u64,
+    },
+    /// Cancel a queued config change before it takes effect
+    CancelConfigChange {
+        id: u64,
+    },
+    /// Apply queued config changes whose delay has passed. They are also
+    /// applied at the start of every other execute message. Callable by
+    /// anyone.
+    ApplyPendingConfigChanges {},
+    /// Add, replace or remove a named bundle of grant configs
+    UpdateGrantBundle {
+        name: String,
+        msg_type_urls: Option<Vec<String>>,
+    },
+    /// Add, replace or remove a named fee tier. Grantees assigned a removed
+    /// tier are treated as unassigned
+    UpdateFeeTier {
+        name: String,
+        tier: Option<FeeTier>,
+    },
+    /// Assign a fee tier to a grantee, or return them to the default
+    SetGranteeTier {
+        grantee: Addr,
+        tier: Option<String>,
+    },
     DeployFeeGrant {
         authz_granter: Addr,
         authz_grantee: Addr,
+        /// Required when the treasury has a sponsor key
+        voucher: Option<Voucher>,
+        /// A grant bundle whose grant configs are required in addition to
+        /// the non-optional ones
+        bundle: Option<String>,
     },
     RevokeAllowance {
         grantee: Addr,
     },
+    /// Stop `DeployFeeGrant`, optionally until a given time. Callable by the
+    /// guardian and the admin
+    Pause {
+        unpause_at: Option<Timestamp>,
+    },
+    /// Lift a pause. Callable by the admin only
+    Unpause {},
+    /// Revoke deployed allowances in batches while paused. Callable by the
+    /// admin only
+    RevokeAllAllowances {
+        limit: Option<u32>,
+    },
     UpdateParams {
         params: Params,
     },
+    /// Withdraw funds not deposited by sponsors
     Withdraw {
         coins: Vec<Coin>,
     },
+    /// Fund the treasury, recording the attached funds as the sender's
+    /// contribution
+    Deposit {},
+    /// Return part of a sponsor's share of what remains of their deposits.
+    /// Their share is their deposit's fraction of the treasury's balance, up
+    /// to the total deposited
+    RefundSponsor {
+        sponsor: Addr,
+        coins: Vec<Coin>,
+    },
+    /// Return what remains of a denom to all sponsors in proportion to their
+    /// deposits, refunding up to `limit` sponsors per call. Deposits and
+    /// refunds of the denom are refused until every sponsor is refunded
+    RefundSponsorsProRata {
+        denom: String,
+        limit: Option<u32>,
+    },
+    /// Revoke fee allowances that have expired or whose grantee no longer
+    /// holds the required authz grants. Callable by anyone, with the prune
+    /// bounty paid for each expired allowance.
+    PruneExpired {
+        limit: Option<u32>,
+    },
+    /// Revoke the fee allowance of a grantee missing an authz grant it was
+    /// required to hold when the allowance was deployed. Grant config changes
+    /// since then don't revoke anything. Callable by anyone.
+    Reconcile {
+        grantee: Addr,
+    },
+    /// `Reconcile` for several grantees at once. Grantees whose grants can't
+    /// be checked are skipped and listed in the `skipped` attribute
+    ReconcileBatch {
+        grantees: Vec<Addr>,
+    },
+    UpdatePruneBounty {
+        bounty: Option<Coin>,
+    },
+    UpdateSponsorKey {
+        sponsor_key: Option<SponsorKey>,
+    },
+    UpdateGrantLimits {
+        limits: GrantLimits,
+    },
+    /// Set or remove the cap on spend limits of fee grants deployed per
+    /// period
+    UpdateSpendBudget {
+        budget: Option<SpendBudget>,
+    },
     Migrate {
         new_code_id: u64,
         migrate_msg: Binary,
     },
 }
 
+/// Queries read stored state. A queued config change whose delay has passed
+/// is only applied by the next execute message, so until then queries return
+/// the config it replaces; `PendingConfigChanges` lists such changes.
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
@@ -61,6 +180,19 @@ pub enum QueryMsg {
     #[returns(Binary)]
     FeeConfig {},
 
+    #[returns(Vec<(String, FeeTier)>)]
+    FeeTiers {},
+
+    #[returns(u64)]
+    ConfigDelay {},
+
+    /// Query config changes waiting to take effect, by id
+    #[returns(Vec<(u64, PendingConfigChange)>)]
+    PendingConfigChanges {},
+
+    #[returns(Option<String>)]
+    GranteeTier { grantee: Addr },
+
     #[returns(Binary)]
     Admin {},
 
@@ -69,6 +201,122 @@ pub enum QueryMsg {
 
     #[returns(Binary)]
     Params {},
+
+    /// Query the typed params metadata, `None` for legacy free-form metadata
+    #[returns(Option<ParamsMetadata>)]
+    Metadata {},
+
+    /// Check whether the grantee's authz grants satisfy the grant configs,
+    /// and the allowance they would receive. Grant limits, the spend budget,
+    /// vouchers and pauses are not checked
+    #[returns(EligibilityResponse)]
+    CheckEligibility {
+        granter: Addr,
+        grantee: Addr,
+        bundle: Option<String>,
+    },
+
+    #[returns(Vec<(String, Vec<String>)>)]
+    GrantBundles {},
+
+    /// Build the authz `MsgGrant`s the granter must sign for the grantee: one
+    /// for each required grant config and each selected optional one. Grants
+    /// expire `expires_in` seconds from now, or never if unset
+    #[returns(Vec<Any>)]
+    GrantMsgs {
+        granter: Addr,
+        grantee: Addr,
+        optional_type_urls: Vec<String>,
+        expires_in: Option<u64>,
+    },
+
+    /// Query the members holding a role, not including the admin
+    #[returns(Vec<Addr>)]
+    RoleMembers { role: Role },
+
+    #[returns(Vec<Addr>)]
+    PendingRoleMembers { role: Role },
+
+    #[returns(Option<Coin>)]
+    PruneBounty {},
+
+    /// Query the current pause, including one whose unpause time has passed
+    #[returns(Option<PauseState>)]
+    Pause {},
+
+    /// Query the deposits a sponsor has not had refunded
+    #[returns(Vec<Coin>)]
+    SponsorDeposits { sponsor: Addr },
+
+    /// Query the sum of all sponsor deposits, which can't be withdrawn
+    #[returns(Vec<Coin>)]
+    TotalDeposits {},
+
+    #[returns(Option<SponsorKey>)]
+    SponsorKey {},
+
+    /// Query whether a voucher nonce has been used. Nonces are scoped by the
+    /// voucher's expiry, and forgotten some time after it
+    #[returns(bool)]
+    VoucherNonceUsed { expiry: Timestamp, nonce: u64 },
+
+    #[returns(GrantLimits)]
+    GrantLimits {},
+
+    #[returns(Option<SpendBudget>)]
+    SpendBudget {},
+
+    /// Query the spend committed and remaining in the current budget period
+    #[returns(Option<BudgetUsageResponse>)]
+    BudgetUsage {},
+
+    /// Query the number of grantees in the registry
+    #[returns(u32)]
+    ActiveGranteeCount {},
+
+    #[returns(Option<GranteeHistory>)]
+    GranteeHistory { grantee: Addr },
+
+    #[returns(GranteeRecord)]
+    Grantee { grantee: Addr },
+
+    /// Query what remains of the fee allowance the treasury has deployed to
+    /// the grantee, read from the chain
+    #[returns(Option<AllowanceReport>)]
+    GranteeAllowance { grantee: Addr },
+
+    /// Query the grantees with a deployed fee grant, ordered by address
+    #[returns(Vec<(Addr, GranteeRecord)>)]
+    Grantees {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+
+    /// Query the grantees with a deployed fee grant, ordered by expiration.
+    /// `start_after` is the expiration in seconds and address of the last
+    /// grantee of the previous page.
+    #[returns(Vec<(Addr, GranteeRecord)>)]
+    GranteesByExpiration {
+        start_after: Option<(u64, Addr)>,
+        limit: Option<u32>,
+    },
+}
+
+#[cw_serde]
+pub struct EligibilityResponse {
+    pub eligible: bool,
+    /// The status of each grant config, by msg type url
+    pub grants: Vec<(String, GrantStatus)>,
+    /// The fee tier the allowance would come from, without a voucher
+    pub tier: Option<String>,
+    /// The allowance `DeployFeeGrant` would issue
+    pub allowance: Option<Any>,
+}
+
+#[cw_serde]
+pub struct BudgetUsageResponse {
+    pub usage: BudgetUsage,
+    pub remaining: Vec<Coin>,
 }
 
 #[cw_serde]
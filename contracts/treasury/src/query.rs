@@ -1,6 +1,27 @@
-use crate::grant::{FeeConfig, GrantConfig};
-use crate::state::{Params, ADMIN, FEE_CONFIG, GRANT_CONFIGS, PARAMS, PENDING_ADMIN};
-use cosmwasm_std::{Addr, Order, StdResult, Storage};
+use crate::execute::{
+    authz_grant_status, build_allowance, bundle_msg_type_urls, parse_metadata, select_fee_config,
+};
+use crate::grant::allowance::{allowance_report, AllowanceReport};
+use crate::grant::authorization::msg_grant;
+use crate::grant::{Any, FeeConfig, FeeTier, GrantConfig, GrantStatus};
+use crate::msg::{BudgetUsageResponse, EligibilityResponse};
+use crate::state::{
+    current_budget_usage, GrantLimits, GranteeHistory, GranteeRecord, Params, ParamsMetadata,
+    PauseState, PendingConfigChange, Role, SpendBudget, ACTIVE_GRANTEE_COUNT, ADMIN, CONFIG_DELAY,
+    FEE_CONFIG, FEE_TIERS, GRANTEES, GRANTEE_HISTORY, GRANTEE_TIERS, GRANT_BUNDLES, GRANT_CONFIGS,
+    GRANT_LIMITS, PARAMS, PAUSE, PENDING_ADMIN, PENDING_CONFIG_CHANGES, PENDING_ROLES,
+    PRUNE_BOUNTY, ROLES, SPEND_BUDGET, SPONSOR_DEPOSITS, SPONSOR_KEY, TOTAL_DEPOSITS,
+    USED_VOUCHER_NONCES,
+};
+use crate::voucher::SponsorKey;
+use cosmos_sdk_proto::cosmos::feegrant::v1beta1::{QueryAllowanceRequest, QueryAllowanceResponse};
+use cosmos_sdk_proto::prost::Message;
+use cosmos_sdk_proto::traits::MessageExt;
+use cosmwasm_std::{Addr, Coin, Deps, Env, Order, StdError, StdResult, Storage, Timestamp};
+use cw_storage_plus::Bound;
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
 
 #[allow(dead_code)]
 pub fn grant_config_type_urls(store: &dyn Storage) -> StdResult<Vec<String>> {
@@ -37,3 +58,296 @@ pub fn pending_admin(store: &dyn Storage) -> StdResult<Addr> {
 pub fn params(store: &dyn Storage) -> StdResult<Params> {
     PARAMS.load(store)
 }
+
+#[allow(dead_code)]
+pub fn role_members(store: &dyn Storage, role: Role) -> StdResult<Vec<Addr>> {
+    ROLES
+        .prefix(role.as_str())
+        .keys(store, None, None, Order::Ascending)
+        .collect()
+}
+
+#[allow(dead_code)]
+pub fn pending_role_members(store: &dyn Storage, role: Role) -> StdResult<Vec<Addr>> {
+    PENDING_ROLES
+        .prefix(role.as_str())
+        .keys(store, None, None, Order::Ascending)
+        .collect()
+}
+
+#[allow(dead_code)]
+pub fn prune_bounty(store: &dyn Storage) -> StdResult<Option<Coin>> {
+    PRUNE_BOUNTY.may_load(store)
+}
+
+#[allow(dead_code)]
+pub fn sponsor_key(store: &dyn Storage) -> StdResult<Option<SponsorKey>> {
+    SPONSOR_KEY.may_load(store)
+}
+
+#[allow(dead_code)]
+pub fn voucher_nonce_used(store: &dyn Storage, expiry: Timestamp, nonce: u64) -> StdResult<bool> {
+    Ok(USED_VOUCHER_NONCES.has(store, (expiry.nanos(), nonce)))
+}
+
+#[allow(dead_code)]
+pub fn grant_limits(store: &dyn Storage) -> StdResult<GrantLimits> {
+    Ok(GRANT_LIMITS.may_load(store)?.unwrap_or_default())
+}
+
+#[allow(dead_code)]
+pub fn check_eligibility(
+    deps: Deps,
+    env: &Env,
+    granter: Addr,
+    grantee: Addr,
+    bundle: Option<String>,
+) -> StdResult<EligibilityResponse> {
+    if let Some(name) = &bundle {
+        if !GRANT_BUNDLES.has(deps.storage, name.clone()) {
+            return Err(StdError::generic_err(format!(
+                "grant bundle {name} not found"
+            )));
+        }
+    }
+    let bundle = bundle_msg_type_urls(deps.storage, &bundle)?;
+    let mut grants = vec![];
+    for item in GRANT_CONFIGS.range(deps.storage, None, None, Order::Ascending) {
+        let (msg_type_url, grant_config) = item?;
+        let status = authz_grant_status(
+            deps,
+            &granter,
+            &grantee,
+            &msg_type_url,
+            &grant_config,
+            &bundle,
+        )
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+        grants.push((msg_type_url, status));
+    }
+    let eligible = grants
+        .iter()
+        .all(|(_, status)| matches!(status, GrantStatus::Present | GrantStatus::MissingOptional));
+
+    let (tier, fee_config) = select_fee_config(deps, &granter, &grantee, None)
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+    let allowance = fee_config
+        .allowance
+        .map(|allowance| build_allowance(env, &grantee, allowance, fee_config.expiration))
+        .transpose()
+        .map_err(|err| StdError::generic_err(err.to_string()))?
+        .map(|(allowance, _)| allowance);
+
+    Ok(EligibilityResponse {
+        eligible,
+        grants,
+        tier,
+        allowance,
+    })
+}
+
+#[allow(dead_code)]
+pub fn grant_msgs(
+    store: &dyn Storage,
+    env: &Env,
+    granter: Addr,
+    grantee: Addr,
+    optional_type_urls: Vec<String>,
+    expires_in: Option<u64>,
+) -> StdResult<Vec<Any>> {
+    for type_url in optional_type_urls.iter() {
+        if !GRANT_CONFIGS.has(store, type_url.clone()) {
+            return Err(StdError::generic_err(format!(
+                "grant config for {type_url} not found"
+            )));
+        }
+    }
+
+    let expiration = expires_in.map(|seconds| env.block.time.plus_seconds(seconds));
+    let mut msgs = vec![];
+    for item in GRANT_CONFIGS.range(store, None, None, Order::Ascending) {
+        let (msg_type_url, grant_config) = item?;
+        if grant_config.optional && !optional_type_urls.contains(&msg_type_url) {
+            continue;
+        }
+        let msg = msg_grant(&granter, &grantee, &grant_config.authorization, expiration)
+            .map_err(|err| StdError::generic_err(err.to_string()))?;
+        msgs.push(msg);
+    }
+
+    Ok(msgs)
+}
+
+#[allow(dead_code)]
+pub fn config_delay(store: &dyn Storage) -> StdResult<u64> {
+    Ok(CONFIG_DELAY.may_load(store)?.unwrap_or_default())
+}
+
+#[allow(dead_code)]
+pub fn pending_config_changes(store: &dyn Storage) -> StdResult<Vec<(u64, PendingConfigChange)>> {
+    PENDING_CONFIG_CHANGES
+        .range(store, None, None, Order::Ascending)
+        .collect()
+}
+
+#[allow(dead_code)]
+pub fn grant_bundles(store: &dyn Storage) -> StdResult<Vec<(String, Vec<String>)>> {
+    GRANT_BUNDLES
+        .range(store, None, None, Order::Ascending)
+        .collect()
+}
+
+#[allow(dead_code)]
+pub fn sponsor_deposits(store: &dyn Storage, sponsor: Addr) -> StdResult<Vec<Coin>> {
+    let mut deposits = Vec::new();
+    for denom in TOTAL_DEPOSITS.keys(store, None, None, Order::Ascending) {
+        let denom = denom?;
+        if let Some(amount) = SPONSOR_DEPOSITS.may_load(store, (&denom, &sponsor))? {
+            deposits.push(Coin::new(amount, denom));
+        }
+    }
+    Ok(deposits)
+}
+
+#[allow(dead_code)]
+pub fn total_deposits(store: &dyn Storage) -> StdResult<Vec<Coin>> {
+    TOTAL_DEPOSITS
+        .range(store, None, None, Order::Ascending)
+        .map(|item| item.map(|(denom, amount)| Coin::new(amount, denom)))
+        .collect()
+}
+
+#[allow(dead_code)]
+pub fn pause(store: &dyn Storage) -> StdResult<Option<PauseState>> {
+    PAUSE.may_load(store)
+}
+
+#[allow(dead_code)]
+pub fn metadata(store: &dyn Storage) -> StdResult<Option<ParamsMetadata>> {
+    let params = PARAMS.load(store)?;
+    parse_metadata(&params.metadata).map_err(|err| StdError::generic_err(err.to_string()))
+}
+
+#[allow(dead_code)]
+pub fn fee_tiers(store: &dyn Storage) -> StdResult<Vec<(String, FeeTier)>> {
+    FEE_TIERS
+        .range(store, None, None, Order::Ascending)
+        .collect()
+}
+
+#[allow(dead_code)]
+pub fn grantee_tier(store: &dyn Storage, grantee: Addr) -> StdResult<Option<String>> {
+    GRANTEE_TIERS.may_load(store, grantee)
+}
+
+#[allow(dead_code)]
+pub fn spend_budget(store: &dyn Storage) -> StdResult<Option<SpendBudget>> {
+    SPEND_BUDGET.may_load(store)
+}
+
+#[allow(dead_code)]
+pub fn budget_usage(store: &dyn Storage, env: Env) -> StdResult<Option<BudgetUsageResponse>> {
+    let Some(budget) = SPEND_BUDGET.may_load(store)? else {
+        return Ok(None);
+    };
+    let usage = current_budget_usage(store, &budget, env.block.time)?;
+    let remaining = budget
+        .amount
+        .into_iter()
+        .map(|coin| {
+            let committed = usage
+                .committed
+                .iter()
+                .find(|c| c.denom == coin.denom)
+                .map_or(0u128.into(), |c| c.amount);
+            Coin::new(coin.amount.saturating_sub(committed), coin.denom)
+        })
+        .collect();
+
+    Ok(Some(BudgetUsageResponse { usage, remaining }))
+}
+
+#[allow(dead_code)]
+pub fn active_grantee_count(store: &dyn Storage) -> StdResult<u32> {
+    Ok(ACTIVE_GRANTEE_COUNT.may_load(store)?.unwrap_or_default())
+}
+
+#[allow(dead_code)]
+pub fn grantee_history(store: &dyn Storage, grantee: Addr) -> StdResult<Option<GranteeHistory>> {
+    GRANTEE_HISTORY.may_load(store, grantee)
+}
+
+#[allow(dead_code)]
+pub fn grantee(store: &dyn Storage, grantee: Addr) -> StdResult<GranteeRecord> {
+    GRANTEES.load(store, grantee)
+}
+
+#[allow(dead_code)]
+pub fn grantees(
+    store: &dyn Storage,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(Addr, GranteeRecord)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    GRANTEES
+        .range(
+            store,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect()
+}
+
+/// Grantees ordered by the expiration of their allowance, soonest first.
+/// Allowances without an expiration are listed last.
+#[allow(dead_code)]
+pub fn grantees_by_expiration(
+    store: &dyn Storage,
+    start_after: Option<(u64, Addr)>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(Addr, GranteeRecord)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    GRANTEES
+        .idx
+        .expiration
+        .range(
+            store,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect()
+}
+
+#[allow(dead_code)]
+pub fn grantee_allowance(
+    deps: Deps,
+    env: &Env,
+    grantee: Addr,
+) -> StdResult<Option<AllowanceReport>> {
+    let query_bz = QueryAllowanceRequest {
+        granter: env.contract.address.to_string(),
+        grantee: grantee.into_string(),
+    }
+    .to_bytes()
+    .map_err(|err| StdError::generic_err(err.to_string()))?;
+    // the chain errors when there is no allowance
+    let Ok(response) = deps.querier.query_grpc(
+        "/cosmos.feegrant.v1beta1.Query/Allowance".to_string(),
+        query_bz.into(),
+    ) else {
+        return Ok(None);
+    };
+    let response = QueryAllowanceResponse::decode(response.as_slice())
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+
+    match response.allowance.and_then(|grant| grant.allowance) {
+        None => Ok(None),
+        Some(allowance) => allowance_report(&allowance.into())
+            .map(Some)
+            .map_err(|err| StdError::generic_err(err.to_string())),
+    }
+}
@@ -1,17 +1,62 @@
-use crate::grant::{FeeConfig, GrantConfig};
+use crate::grant::{FeeConfig, FeeTier, GrantConfig};
+use crate::voucher::SponsorKey;
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::Addr;
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Coin, StdResult, Storage, Timestamp, Uint128};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+use std::collections::BTreeMap;
 
 // msg_type_url to grant config
 pub const GRANT_CONFIGS: Map<String, GrantConfig> = Map::new("grant_configs");
 
 pub const FEE_CONFIG: Item<FeeConfig> = Item::new("fee_config");
 
+// incremented on every fee config update, starting from 0
+pub const FEE_CONFIG_VERSION: Item<u64> = Item::new("fee_config_version");
+
+// tier name to fee tier
+pub const FEE_TIERS: Map<String, FeeTier> = Map::new("fee_tiers");
+
+// bundle name to the msg type urls of the grant configs it requires
+pub const GRANT_BUNDLES: Map<String, Vec<String>> = Map::new("grant_bundles");
+
+// grantee to the name of the fee tier assigned to them
+pub const GRANTEE_TIERS: Map<Addr, String> = Map::new("grantee_tiers");
+
 pub const ADMIN: Item<Addr> = Item::new("admin");
 
 pub const PENDING_ADMIN: Item<Addr> = Item::new("pending_admin");
 
+/// Administrative permissions the admin can delegate. The admin implicitly
+/// holds every role.
+#[cw_serde]
+pub enum Role {
+    /// Manages grant configs, the fee config and params
+    ConfigManager,
+    /// Revokes allowances
+    Operator,
+    /// Withdraws funds and sets bounties paid by the treasury
+    Finance,
+    /// Pauses fee grant deployment during an incident, but can't unpause or
+    /// revoke deployed allowances
+    Guardian,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::ConfigManager => "config_manager",
+            Role::Operator => "operator",
+            Role::Finance => "finance",
+            Role::Guardian => "guardian",
+        }
+    }
+}
+
+// (role, member) to whether the role has been accepted
+pub const ROLES: Map<(&str, &Addr), bool> = Map::new("roles");
+
+pub const PENDING_ROLES: Map<(&str, &Addr), bool> = Map::new("pending_roles");
+
 #[cw_serde]
 pub struct Params {
     pub redirect_url: String,
@@ -20,3 +65,248 @@ pub struct Params {
 }
 
 pub const PARAMS: Item<Params> = Item::new("params");
+
+pub const METADATA_VERSION: u32 = 1;
+
+/// The typed form of `Params.metadata`, rendered by wallets on their consent
+/// screen. Metadata without a `schema_version` is free-form JSON, such as
+/// from before this schema existed.
+#[cw_serde]
+pub struct ParamsMetadata {
+    pub schema_version: u32,
+    pub display_name: String,
+    pub description: Option<String>,
+    pub terms_of_service_url: Option<String>,
+    pub privacy_policy_url: Option<String>,
+    /// Human readable descriptions of grant configs, by msg type url
+    #[serde(default)]
+    pub msg_descriptions: BTreeMap<String, String>,
+}
+
+#[cw_serde]
+pub struct GranteeRecord {
+    pub granter: Addr,
+    pub deployed_at: Timestamp,
+    pub expiration: Option<Timestamp>,
+    pub fee_config_version: u64,
+    /// The fee tier the allowance was deployed from, if not the default
+    pub tier: Option<String>,
+    /// The grant bundle the grantee opted into
+    pub bundle: Option<String>,
+    /// The msg type urls of the grant configs the grantee had to hold authz
+    /// grants for when the allowance was deployed
+    pub required_grants: Vec<String>,
+}
+
+pub struct GranteeIndexes<'a> {
+    // expiration in seconds, with u64::MAX for allowances that never expire
+    pub expiration: MultiIndex<'a, u64, GranteeRecord, Addr>,
+}
+
+impl IndexList<GranteeRecord> for GranteeIndexes<'_> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<GranteeRecord>> + '_> {
+        let v: Vec<&dyn Index<GranteeRecord>> = vec![&self.expiration];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Removes a grantee from the registry, keeping the active grantee count.
+pub fn remove_grantee(storage: &mut dyn Storage, grantee: &Addr) -> StdResult<()> {
+    if GRANTEES.has(storage, grantee.clone()) {
+        GRANTEES.remove(storage, grantee.clone())?;
+        let count = ACTIVE_GRANTEE_COUNT.may_load(storage)?.unwrap_or_default();
+        ACTIVE_GRANTEE_COUNT.save(storage, &count.saturating_sub(1))?;
+    }
+    Ok(())
+}
+
+/// Saves a grantee to the registry, keeping the active grantee count.
+pub fn save_grantee(
+    storage: &mut dyn Storage,
+    grantee: &Addr,
+    record: &GranteeRecord,
+) -> StdResult<()> {
+    if !GRANTEES.has(storage, grantee.clone()) {
+        let count = ACTIVE_GRANTEE_COUNT.may_load(storage)?.unwrap_or_default();
+        ACTIVE_GRANTEE_COUNT.save(storage, &(count + 1))?;
+    }
+    GRANTEES.save(storage, grantee.clone(), record)
+}
+
+// grantee to the fee grant deployed for them
+pub const GRANTEES: IndexedMap<Addr, GranteeRecord, GranteeIndexes> = IndexedMap::new(
+    "grantees",
+    GranteeIndexes {
+        expiration: MultiIndex::new(
+            |_pk, record| record.expiration.map_or(u64::MAX, |e| e.seconds()),
+            "grantees",
+            "grantees__expiration",
+        ),
+    },
+);
+
+// paid to the caller of `PruneExpired` for each expired allowance pruned
+pub const PRUNE_BOUNTY: Item<Coin> = Item::new("prune_bounty");
+
+// the last grantee checked for missing authz grants by `PruneExpired`
+pub const PRUNE_CURSOR: Item<Addr> = Item::new("prune_cursor");
+
+pub const SPONSOR_KEY: Item<SponsorKey> = Item::new("sponsor_key");
+
+// (expiry in nanoseconds, nonce) of consumed vouchers, pruned once expired
+pub const USED_VOUCHER_NONCES: Map<(u64, u64), bool> = Map::new("used_voucher_nonces");
+
+#[cw_serde]
+#[derive(Default)]
+pub struct GrantLimits {
+    /// Minimum seconds between two fee grant deployments to the same grantee
+    pub min_deploy_interval: Option<u64>,
+    /// Maximum number of fee grant deployments to a grantee over its lifetime
+    pub max_deploys_per_grantee: Option<u32>,
+    /// Maximum number of grantees in the registry at once
+    pub max_active_grantees: Option<u32>,
+}
+
+pub const GRANT_LIMITS: Item<GrantLimits> = Item::new("grant_limits");
+
+// the number of entries in GRANTEES
+pub const ACTIVE_GRANTEE_COUNT: Item<u32> = Item::new("active_grantee_count");
+
+/// Kept for the lifetime of the treasury, unlike the grantee registry.
+#[cw_serde]
+pub struct GranteeHistory {
+    pub deploy_count: u32,
+    pub last_deployed_at: Timestamp,
+}
+
+pub const GRANTEE_HISTORY: Map<Addr, GranteeHistory> = Map::new("grantee_history");
+
+/// A cap on the spend limits of fee grants deployed within each period.
+#[cw_serde]
+pub struct SpendBudget {
+    pub amount: Vec<Coin>,
+    /// Length of a budget period in seconds, 0 for a budget that never resets
+    pub period: u64,
+}
+
+pub const SPEND_BUDGET: Item<SpendBudget> = Item::new("spend_budget");
+
+/// Spend limits committed to fee grants in the current budget period.
+#[cw_serde]
+pub struct BudgetUsage {
+    pub period_start: Timestamp,
+    pub committed: Vec<Coin>,
+}
+
+pub const BUDGET_USAGE: Item<BudgetUsage> = Item::new("budget_usage");
+
+/// The budget usage as of `now`, starting a new period once the current one
+/// has elapsed. Periods are aligned to the first one.
+pub fn current_budget_usage(
+    storage: &dyn Storage,
+    budget: &SpendBudget,
+    now: Timestamp,
+) -> StdResult<BudgetUsage> {
+    match BUDGET_USAGE.may_load(storage)? {
+        Some(usage)
+            if budget.period == 0 || now < usage.period_start.plus_seconds(budget.period) =>
+        {
+            Ok(usage)
+        }
+        Some(usage) => {
+            let elapsed = now.seconds() - usage.period_start.seconds();
+            Ok(BudgetUsage {
+                period_start: usage
+                    .period_start
+                    .plus_seconds(elapsed - elapsed % budget.period),
+                committed: vec![],
+            })
+        }
+        None => Ok(BudgetUsage {
+            period_start: now,
+            committed: vec![],
+        }),
+    }
+}
+
+// seconds a config change waits before taking effect, 0 or unset to apply
+// changes immediately
+pub const CONFIG_DELAY: Item<u64> = Item::new("config_delay");
+
+/// The longest `CONFIG_DELAY` that can be set, 30 days.
+pub const MAX_CONFIG_DELAY: u64 = 30 * 24 * 60 * 60;
+
+#[cw_serde]
+pub enum ConfigChange {
+    UpdateGrantConfig {
+        msg_type_url: String,
+        grant_config: GrantConfig,
+    },
+    RemoveGrantConfig {
+        msg_type_url: String,
+    },
+    UpdateFeeConfig {
+        fee_config: FeeConfig,
+    },
+    UpdateConfigDelay {
+        seconds: u64,
+    },
+    UpdateFeeTier {
+        name: String,
+        tier: Option<FeeTier>,
+    },
+    UpdateSponsorKey {
+        sponsor_key: Option<SponsorKey>,
+    },
+    UpdateGrantLimits {
+        limits: GrantLimits,
+    },
+    UpdateSpendBudget {
+        budget: Option<SpendBudget>,
+    },
+}
+
+#[cw_serde]
+pub struct PendingConfigChange {
+    pub change: ConfigChange,
+    pub activates_at: Timestamp,
+}
+
+pub const PENDING_CONFIG_CHANGES: Map<u64, PendingConfigChange> =
+    Map::new("pending_config_changes");
+
+pub const NEXT_CONFIG_CHANGE_ID: Item<u64> = Item::new("next_config_change_id");
+
+// (denom, sponsor) to the amount the sponsor has deposited and not had refunded
+pub const SPONSOR_DEPOSITS: Map<(&str, &Addr), Uint128> = Map::new("sponsor_deposits");
+
+// denom to the sum of SPONSOR_DEPOSITS, which the admin cannot withdraw
+pub const TOTAL_DEPOSITS: Map<&str, Uint128> = Map::new("total_deposits");
+
+/// A pro-rata refund of a denom paid out over several batches.
+#[cw_serde]
+pub struct ProRataRefund {
+    /// The amount being refunded to all sponsors when the refund started
+    pub refundable: Uint128,
+    /// The total deposited when the refund started
+    pub total: Uint128,
+}
+
+// denom to its pro-rata refund in progress
+pub const PRO_RATA_REFUNDS: Map<&str, ProRataRefund> = Map::new("pro_rata_refunds");
+
+#[cw_serde]
+pub struct PauseState {
+    pub paused_at: Timestamp,
+    /// When the pause lifts by itself, if ever
+    pub unpause_at: Option<Timestamp>,
+}
+
+pub const PAUSE: Item<PauseState> = Item::new("pause");
+
+/// Whether fee grant deployment is paused at `now`.
+pub fn is_paused(storage: &dyn Storage, now: Timestamp) -> StdResult<bool> {
+    Ok(PAUSE
+        .may_load(storage)?
+        .is_some_and(|pause| pause.unpause_at.is_none_or(|unpause_at| now < unpause_at)))
+}
@@ -0,0 +1,365 @@
+use crate::error::ContractError::{
+    InvalidSponsorKey, InvalidVoucherSignature, VoucherExpired, VoucherNonceUsed, VoucherRequired,
+};
+use crate::error::ContractResult;
+use crate::state::{SPONSOR_KEY, USED_VOUCHER_NONCES};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{to_json_vec, Addr, Binary, DepsMut, Env, Order, StdResult, Timestamp};
+use cw_storage_plus::Bound;
+use sha2::{Digest, Sha256};
+
+// expired nonces forgotten by each consumed voucher, keeping the nonce set
+// bounded by the vouchers live at once
+const MAX_PRUNED_NONCES: usize = 10;
+
+/// The key of the sponsor backend, whose vouchers are required to deploy a
+/// fee grant when set.
+#[cw_serde]
+pub enum SponsorKey {
+    Secp256K1 { pubkey: Binary },
+    Ed25519 { pubkey: Binary },
+}
+
+impl SponsorKey {
+    /// Checks the pubkey has the length and encoding its key type expects.
+    pub fn validate(&self) -> ContractResult<()> {
+        match self {
+            // compressed or uncompressed SEC1
+            SponsorKey::Secp256K1 { pubkey } => match (pubkey.len(), pubkey.first()) {
+                (33, Some(0x02 | 0x03)) | (65, Some(0x04)) => Ok(()),
+                _ => Err(InvalidSponsorKey {
+                    msg: "secp256k1 pubkey must be a 33 or 65 byte SEC1 encoding".to_string(),
+                }),
+            },
+            SponsorKey::Ed25519 { pubkey } if pubkey.len() == 32 => Ok(()),
+            SponsorKey::Ed25519 { .. } => Err(InvalidSponsorKey {
+                msg: "ed25519 pubkey must be 32 bytes".to_string(),
+            }),
+        }
+    }
+}
+
+#[cw_serde]
+pub struct Voucher {
+    pub expiry: Timestamp,
+    pub nonce: u64,
+    /// The fee tier to deploy the grantee's allowance from
+    pub tier: Option<String>,
+    /// Signature over the sha256 hash of the JSON encoded `VoucherPayload`
+    pub signature: Binary,
+}
+
+/// The data a sponsor signs to issue a voucher.
+#[cw_serde]
+pub struct VoucherPayload {
+    pub treasury: Addr,
+    pub grantee: Addr,
+    pub expiry: Timestamp,
+    pub nonce: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tier: Option<String>,
+}
+
+/// Verifies the voucher for the grantee if the treasury has a sponsor key,
+/// consuming its nonce. Returns the fee tier of a verified voucher.
+///
+/// A nonce only needs to be remembered until its voucher expires, so the
+/// used nonces are keyed by expiry and the expired ones are pruned here.
+pub fn consume_voucher(
+    deps: DepsMut,
+    env: &Env,
+    grantee: &Addr,
+    voucher: Option<Voucher>,
+) -> ContractResult<Option<String>> {
+    let sponsor_key = match SPONSOR_KEY.may_load(deps.storage)? {
+        None => return Ok(None),
+        Some(key) => key,
+    };
+    let voucher = voucher.ok_or(VoucherRequired)?;
+
+    if voucher.expiry <= env.block.time {
+        return Err(VoucherExpired);
+    }
+    let key = (voucher.expiry.nanos(), voucher.nonce);
+    if USED_VOUCHER_NONCES.has(deps.storage, key) {
+        return Err(VoucherNonceUsed {
+            nonce: voucher.nonce,
+        });
+    }
+
+    let payload = to_json_vec(&VoucherPayload {
+        treasury: env.contract.address.clone(),
+        grantee: grantee.clone(),
+        expiry: voucher.expiry,
+        nonce: voucher.nonce,
+        tier: voucher.tier.clone(),
+    })?;
+    let hash = Sha256::digest(payload);
+
+    let verified = match sponsor_key {
+        SponsorKey::Secp256K1 { pubkey } => {
+            deps.api
+                .secp256k1_verify(&hash, &voucher.signature, &pubkey)?
+        }
+        SponsorKey::Ed25519 { pubkey } => {
+            deps.api
+                .ed25519_verify(&hash, &voucher.signature, &pubkey)?
+        }
+    };
+    if !verified {
+        return Err(InvalidVoucherSignature);
+    }
+
+    let expired: Vec<(u64, u64)> = USED_VOUCHER_NONCES
+        .keys(
+            deps.storage,
+            None,
+            Some(Bound::inclusive((env.block.time.nanos(), u64::MAX))),
+            Order::Ascending,
+        )
+        .take(MAX_PRUNED_NONCES)
+        .collect::<StdResult<_>>()?;
+    for key in expired {
+        USED_VOUCHER_NONCES.remove(deps.storage, key);
+    }
+    USED_VOUCHER_NONCES.save(deps.storage, key, &true)?;
+
+    Ok(voucher.tier)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::ContractError;
+    use crate::execute::update_sponsor_key;
+    use crate::state::{ADMIN, SPONSOR_KEY, USED_VOUCHER_NONCES};
+    use crate::voucher::{consume_voucher, SponsorKey, Voucher, VoucherPayload};
+    use cosmwasm_std::testing::{message_info, mock_dependencies, mock_env};
+    use cosmwasm_std::{to_json_vec, Addr, Binary, Env, Timestamp};
+    use k256::ecdsa::signature::hazmat::PrehashSigner;
+    use sha2::{Digest, Sha256};
+
+    fn payload_hash(
+        env: &Env,
+        grantee: &Addr,
+        expiry: Timestamp,
+        nonce: u64,
+        tier: Option<&str>,
+    ) -> [u8; 32] {
+        let payload = to_json_vec(&VoucherPayload {
+            treasury: env.contract.address.clone(),
+            grantee: grantee.clone(),
+            expiry,
+            nonce,
+            tier: tier.map(String::from),
+        })
+        .unwrap();
+        Sha256::digest(payload).into()
+    }
+
+    fn secp256k1_voucher(
+        key: &k256::ecdsa::SigningKey,
+        hash: [u8; 32],
+        expiry: Timestamp,
+        nonce: u64,
+        tier: Option<&str>,
+    ) -> Voucher {
+        let signature: k256::ecdsa::Signature = key.sign_prehash(&hash).unwrap();
+        Voucher {
+            expiry,
+            nonce,
+            tier: tier.map(String::from),
+            signature: signature.to_bytes().to_vec().into(),
+        }
+    }
+
+    fn secp256k1_key(key: &k256::ecdsa::SigningKey) -> SponsorKey {
+        SponsorKey::Secp256K1 {
+            pubkey: key.verifying_key().to_sec1_bytes().to_vec().into(),
+        }
+    }
+
+    #[test]
+    fn test_consume_voucher_secp256k1() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let grantee = deps.api.addr_make("grantee");
+        let other = deps.api.addr_make("other");
+        let key = k256::ecdsa::SigningKey::from_slice(&[1; 32]).unwrap();
+        let wrong_key = k256::ecdsa::SigningKey::from_slice(&[2; 32]).unwrap();
+        let expiry = env.block.time.plus_seconds(60);
+
+        // without a sponsor key, vouchers aren't needed
+        assert_eq!(
+            consume_voucher(deps.as_mut(), &env, &grantee, None).unwrap(),
+            None
+        );
+        SPONSOR_KEY
+            .save(deps.as_mut().storage, &secp256k1_key(&key))
+            .unwrap();
+        let err = consume_voucher(deps.as_mut(), &env, &grantee, None).unwrap_err();
+        assert!(matches!(err, ContractError::VoucherRequired));
+
+        let hash = payload_hash(&env, &grantee, expiry, 1, Some("gold"));
+        for invalid in [
+            // signed by another key
+            secp256k1_voucher(&wrong_key, hash, expiry, 1, Some("gold")),
+            // signed for another grantee
+            secp256k1_voucher(
+                &key,
+                payload_hash(&env, &other, expiry, 1, Some("gold")),
+                expiry,
+                1,
+                Some("gold"),
+            ),
+            // a tier or nonce that wasn't signed
+            secp256k1_voucher(&key, hash, expiry, 1, Some("platinum")),
+            secp256k1_voucher(&key, hash, expiry, 2, Some("gold")),
+        ] {
+            let err = consume_voucher(deps.as_mut(), &env, &grantee, Some(invalid)).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidVoucherSignature));
+        }
+
+        // the signed tier is returned for the fee config selection
+        let voucher = secp256k1_voucher(&key, hash, expiry, 1, Some("gold"));
+        assert_eq!(
+            consume_voucher(deps.as_mut(), &env, &grantee, Some(voucher.clone())).unwrap(),
+            Some("gold".to_string())
+        );
+        let err = consume_voucher(deps.as_mut(), &env, &grantee, Some(voucher)).unwrap_err();
+        assert!(matches!(err, ContractError::VoucherNonceUsed { nonce: 1 }));
+
+        let expired = env.block.time;
+        let voucher = secp256k1_voucher(
+            &key,
+            payload_hash(&env, &grantee, expired, 3, None),
+            expired,
+            3,
+            None,
+        );
+        let err = consume_voucher(deps.as_mut(), &env, &grantee, Some(voucher)).unwrap_err();
+        assert!(matches!(err, ContractError::VoucherExpired));
+    }
+
+    #[test]
+    fn test_consume_voucher_ed25519() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let grantee = deps.api.addr_make("grantee");
+        let key = ed25519_zebra::SigningKey::from([3; 32]);
+        let pubkey: [u8; 32] = ed25519_zebra::VerificationKey::from(&key).into();
+        SPONSOR_KEY
+            .save(
+                deps.as_mut().storage,
+                &SponsorKey::Ed25519 {
+                    pubkey: pubkey.to_vec().into(),
+                },
+            )
+            .unwrap();
+        let expiry = env.block.time.plus_seconds(60);
+        let sign = |hash: [u8; 32]| -> Binary {
+            let signature: [u8; 64] = key.sign(&hash).into();
+            signature.to_vec().into()
+        };
+
+        let voucher = Voucher {
+            expiry,
+            nonce: 1,
+            tier: None,
+            signature: sign(payload_hash(&env, &grantee, expiry, 1, None)),
+        };
+        assert_eq!(
+            consume_voucher(deps.as_mut(), &env, &grantee, Some(voucher)).unwrap(),
+            None
+        );
+
+        let voucher = Voucher {
+            expiry,
+            nonce: 2,
+            tier: None,
+            signature: sign(payload_hash(&env, &grantee, expiry, 1, None)),
+        };
+        let err = consume_voucher(deps.as_mut(), &env, &grantee, Some(voucher)).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidVoucherSignature));
+    }
+
+    #[test]
+    fn test_expired_nonces_pruned() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let grantee = deps.api.addr_make("grantee");
+        let key = k256::ecdsa::SigningKey::from_slice(&[1; 32]).unwrap();
+        SPONSOR_KEY
+            .save(deps.as_mut().storage, &secp256k1_key(&key))
+            .unwrap();
+
+        let first_expiry = env.block.time.plus_seconds(10);
+        let voucher = secp256k1_voucher(
+            &key,
+            payload_hash(&env, &grantee, first_expiry, 1, None),
+            first_expiry,
+            1,
+            None,
+        );
+        consume_voucher(deps.as_mut(), &env, &grantee, Some(voucher)).unwrap();
+
+        // a nonce is kept until its voucher expires
+        env.block.time = first_expiry;
+        let expiry = env.block.time.plus_seconds(10);
+        let voucher = secp256k1_voucher(
+            &key,
+            payload_hash(&env, &grantee, expiry, 2, None),
+            expiry,
+            2,
+            None,
+        );
+        assert!(USED_VOUCHER_NONCES.has(&deps.storage, (first_expiry.nanos(), 1)));
+        consume_voucher(deps.as_mut(), &env, &grantee, Some(voucher)).unwrap();
+        assert!(!USED_VOUCHER_NONCES.has(&deps.storage, (first_expiry.nanos(), 1)));
+        assert!(USED_VOUCHER_NONCES.has(&deps.storage, (expiry.nanos(), 2)));
+    }
+
+    #[test]
+    fn test_sponsor_key_validation() {
+        let mut deps = mock_dependencies();
+        let admin = deps.api.addr_make("admin");
+        ADMIN.save(deps.as_mut().storage, &admin).unwrap();
+        let key = k256::ecdsa::SigningKey::from_slice(&[1; 32]).unwrap();
+        let uncompressed = key.verifying_key().to_encoded_point(false);
+
+        for valid in [
+            secp256k1_key(&key),
+            SponsorKey::Secp256K1 {
+                pubkey: uncompressed.as_bytes().to_vec().into(),
+            },
+            SponsorKey::Ed25519 {
+                pubkey: vec![1; 32].into(),
+            },
+        ] {
+            valid.validate().unwrap();
+        }
+
+        let mut wrong_prefix = key.verifying_key().to_sec1_bytes().to_vec();
+        wrong_prefix[0] = 0x04;
+        for invalid in [
+            SponsorKey::Secp256K1 {
+                pubkey: wrong_prefix.into(),
+            },
+            SponsorKey::Secp256K1 {
+                pubkey: vec![2; 32].into(),
+            },
+            SponsorKey::Ed25519 {
+                pubkey: vec![1; 33].into(),
+            },
+        ] {
+            let err = update_sponsor_key(
+                deps.as_mut(),
+                mock_env(),
+                message_info(&admin, &[]),
+                Some(invalid),
+            )
+            .unwrap_err();
+            assert!(matches!(err, ContractError::InvalidSponsorKey { .. }));
+        }
+        assert!(SPONSOR_KEY.may_load(&deps.storage).unwrap().is_none());
+    }
+}
@@ -0,0 +1,12 @@
+mod test_check_eligibility;
+mod test_deploy_fee_grant;
+mod test_grant_bundles;
+mod test_grant_limits;
+mod test_grant_msgs;
+mod test_grantee_allowance;
+mod test_grantees;
+mod test_helpers;
+mod test_prune_expired;
+mod test_reconcile;
+mod test_revoke_all_allowances;
+mod test_spend_budget;
@@ -0,0 +1,102 @@
+use crate::tests::test_helpers::{
+    grant, grant_authorization, setup_app, setup_treasury, MSG_DELEGATE, MSG_SEND,
+};
+use cosmos_sdk_proto::cosmos::bank::v1beta1::SendAuthorization;
+use cosmos_sdk_proto::prost::Message;
+use cosmos_sdk_proto::Any;
+use cosmwasm_std::{Addr, StdResult};
+use cw_multi_test::Executor;
+use treasury::grant::GrantStatus;
+use treasury::msg::{EligibilityResponse, ExecuteMsg, QueryMsg};
+use xion_mock::XionApp;
+
+fn check_eligibility(
+    app: &XionApp,
+    treasury: &Addr,
+    granter: &Addr,
+    grantee: &Addr,
+    bundle: Option<&str>,
+) -> StdResult<EligibilityResponse> {
+    app.wrap().query_wasm_smart(
+        treasury,
+        &QueryMsg::CheckEligibility {
+            granter: granter.clone(),
+            grantee: grantee.clone(),
+            bundle: bundle.map(String::from),
+        },
+    )
+}
+
+#[test]
+fn test_check_eligibility() {
+    let mut app = setup_app();
+    let admin = app.api().addr_make("admin");
+    let user = app.api().addr_make("user");
+    let session = app.api().addr_make("session");
+    let treasury = setup_treasury(
+        &mut app,
+        &admin,
+        &[(MSG_SEND, false), (MSG_DELEGATE, true)],
+        Some(3600),
+    );
+    app.execute_contract(
+        admin.clone(),
+        treasury.clone(),
+        &ExecuteMsg::UpdateGrantBundle {
+            name: "staking".to_string(),
+            msg_type_urls: Some(vec![MSG_DELEGATE.to_string()]),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let res = check_eligibility(&app, &treasury, &user, &session, None).unwrap();
+    assert!(!res.eligible);
+    assert_eq!(
+        res.grants,
+        vec![
+            (MSG_SEND.to_string(), GrantStatus::Missing),
+            (MSG_DELEGATE.to_string(), GrantStatus::MissingOptional),
+        ]
+    );
+
+    grant(&mut app, &user, &session, MSG_SEND, None);
+    let res = check_eligibility(&app, &treasury, &user, &session, None).unwrap();
+    assert!(res.eligible);
+    assert_eq!(res.tier, None);
+    assert_eq!(
+        res.allowance.unwrap().type_url,
+        "/cosmos.feegrant.v1beta1.BasicAllowance"
+    );
+
+    // a bundle makes its optional configs required
+    let res = check_eligibility(&app, &treasury, &user, &session, Some("staking")).unwrap();
+    assert!(!res.eligible);
+    assert_eq!(
+        res.grants[1],
+        (MSG_DELEGATE.to_string(), GrantStatus::Missing)
+    );
+    check_eligibility(&app, &treasury, &user, &session, Some("unknown")).unwrap_err();
+
+    // a grant broader than its config doesn't satisfy it
+    let send_authorization = SendAuthorization {
+        spend_limit: vec![],
+        allow_list: vec![],
+    };
+    grant_authorization(
+        &mut app,
+        &user,
+        &session,
+        Any {
+            type_url: "/cosmos.bank.v1beta1.SendAuthorization".to_string(),
+            value: send_authorization.encode_to_vec(),
+        },
+        None,
+    );
+    let res = check_eligibility(&app, &treasury, &user, &session, None).unwrap();
+    assert!(!res.eligible);
+    assert_eq!(
+        res.grants[0],
+        (MSG_SEND.to_string(), GrantStatus::Mismatched)
+    );
+}
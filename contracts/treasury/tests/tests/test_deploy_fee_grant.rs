@@ -0,0 +1,40 @@
+use crate::tests::test_helpers::{deploy, grant, setup_app, setup_treasury, MSG_SEND};
+use treasury::grant::allowance::AllowanceReport;
+use treasury::msg::QueryMsg;
+use xion_mock::load_allowance;
+
+#[test]
+fn test_deploy_fee_grant() {
+    let mut app = setup_app();
+    let admin = app.api().addr_make("admin");
+    let user = app.api().addr_make("user");
+    let session = app.api().addr_make("session");
+    let treasury = setup_treasury(&mut app, &admin, &[(MSG_SEND, false)], Some(3600));
+
+    // the user has not granted the session key the configured authorization
+    deploy(&mut app, &treasury, &user, &session, None).unwrap_err();
+
+    grant(&mut app, &user, &session, MSG_SEND, None);
+    deploy(&mut app, &treasury, &user, &session, None).unwrap();
+
+    let deployed = load_allowance(app.storage(), treasury.as_str(), session.as_str())
+        .unwrap()
+        .unwrap();
+    assert_eq!(deployed.type_url, "/cosmos.feegrant.v1beta1.BasicAllowance");
+    let report: Option<AllowanceReport> = app
+        .wrap()
+        .query_wasm_smart(
+            &treasury,
+            &QueryMsg::GranteeAllowance {
+                grantee: session.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        report.unwrap().spend_limit,
+        Some(vec![cosmwasm_std::coin(1000, "uxion")])
+    );
+
+    // redeploying replaces the existing allowance
+    deploy(&mut app, &treasury, &user, &session, None).unwrap();
+}
@@ -0,0 +1,79 @@
+use crate::tests::test_helpers::{
+    assert_error, deploy, grant, setup_app, setup_treasury, MSG_DELEGATE, MSG_SEND,
+};
+use cosmwasm_std::Addr;
+use cw_multi_test::Executor;
+use treasury::msg::{ExecuteMsg, QueryMsg};
+use treasury::GranteeRecord;
+
+fn grantee_record(app: &xion_mock::XionApp, treasury: &Addr, grantee: &Addr) -> GranteeRecord {
+    app.wrap()
+        .query_wasm_smart(
+            treasury,
+            &QueryMsg::Grantee {
+                grantee: grantee.clone(),
+            },
+        )
+        .unwrap()
+}
+
+#[test]
+fn test_grant_bundles() {
+    let mut app = setup_app();
+    let admin = app.api().addr_make("admin");
+    let user = app.api().addr_make("user");
+    let staker = app.api().addr_make("staker");
+    let session = app.api().addr_make("session");
+    let treasury = setup_treasury(
+        &mut app,
+        &admin,
+        &[(MSG_SEND, false), (MSG_DELEGATE, true)],
+        None,
+    );
+
+    // a bundle may only name existing grant configs
+    let result = app.execute_contract(
+        admin.clone(),
+        treasury.clone(),
+        &ExecuteMsg::UpdateGrantBundle {
+            name: "staking".to_string(),
+            msg_type_urls: Some(vec!["/unknown".to_string()]),
+        },
+        &[],
+    );
+    assert_error(result, "grant config for /unknown not found");
+    app.execute_contract(
+        admin.clone(),
+        treasury.clone(),
+        &ExecuteMsg::UpdateGrantBundle {
+            name: "staking".to_string(),
+            msg_type_urls: Some(vec![MSG_DELEGATE.to_string()]),
+        },
+        &[],
+    )
+    .unwrap();
+
+    grant(&mut app, &user, &staker, MSG_SEND, None);
+    assert_error(
+        deploy(&mut app, &treasury, &user, &staker, Some("unknown")),
+        "grant bundle unknown not found",
+    );
+
+    // opting into the bundle makes its optional grant config required
+    deploy(&mut app, &treasury, &user, &staker, Some("staking")).unwrap_err();
+    grant(&mut app, &user, &staker, MSG_DELEGATE, None);
+    deploy(&mut app, &treasury, &user, &staker, Some("staking")).unwrap();
+    let record = grantee_record(&app, &treasury, &staker);
+    assert_eq!(record.bundle, Some("staking".to_string()));
+    assert_eq!(
+        record.required_grants,
+        vec![MSG_SEND.to_string(), MSG_DELEGATE.to_string()]
+    );
+
+    // without the bundle it stays optional
+    grant(&mut app, &user, &session, MSG_SEND, None);
+    deploy(&mut app, &treasury, &user, &session, None).unwrap();
+    let record = grantee_record(&app, &treasury, &session);
+    assert_eq!(record.bundle, None);
+    assert_eq!(record.required_grants, vec![MSG_SEND.to_string()]);
+}
@@ -0,0 +1,66 @@
+use crate::tests::test_helpers::{
+    assert_error, deploy, grant, setup_app, setup_treasury, MSG_SEND,
+};
+use cw_multi_test::Executor;
+use treasury::msg::ExecuteMsg;
+use treasury::GrantLimits;
+
+#[test]
+fn test_grant_limits() {
+    let mut app = setup_app();
+    let admin = app.api().addr_make("admin");
+    let user = app.api().addr_make("user");
+    let treasury = setup_treasury(&mut app, &admin, &[(MSG_SEND, false)], None);
+    app.execute_contract(
+        admin.clone(),
+        treasury.clone(),
+        &ExecuteMsg::UpdateGrantLimits {
+            limits: GrantLimits {
+                min_deploy_interval: Some(100),
+                max_deploys_per_grantee: Some(3),
+                max_active_grantees: Some(2),
+            },
+        },
+        &[],
+    )
+    .unwrap();
+    let [a, b, c] = ["a", "b", "c"].map(|name| app.api().addr_make(name));
+    for grantee in [&a, &b, &c] {
+        grant(&mut app, &user, grantee, MSG_SEND, None);
+    }
+
+    deploy(&mut app, &treasury, &user, &a, None).unwrap();
+    let next_deploy_at = app.block_info().time.plus_seconds(100).seconds();
+    app.update_block(|block| block.time = block.time.plus_seconds(99));
+    assert_error(
+        deploy(&mut app, &treasury, &user, &a, None),
+        &format!("grantee cannot receive another fee grant before {next_deploy_at}"),
+    );
+
+    // the lifetime cap counts every deployment, replacements included
+    for _ in 0..2 {
+        app.update_block(|block| block.time = block.time.plus_seconds(100));
+        deploy(&mut app, &treasury, &user, &a, None).unwrap();
+    }
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+    assert_error(
+        deploy(&mut app, &treasury, &user, &a, None),
+        "grantee has reached the limit of 3 fee grants",
+    );
+
+    deploy(&mut app, &treasury, &user, &b, None).unwrap();
+    assert_error(
+        deploy(&mut app, &treasury, &user, &c, None),
+        "treasury has reached the limit of 2 active grantees",
+    );
+
+    // revoking an allowance frees its place
+    app.execute_contract(
+        admin.clone(),
+        treasury.clone(),
+        &ExecuteMsg::RevokeAllowance { grantee: b },
+        &[],
+    )
+    .unwrap();
+    deploy(&mut app, &treasury, &user, &c, None).unwrap();
+}
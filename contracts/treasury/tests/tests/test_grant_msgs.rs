@@ -0,0 +1,89 @@
+use crate::tests::test_helpers::{
+    deploy, generic_authorization, setup_app, setup_treasury, MSG_DELEGATE, MSG_SEND,
+};
+use cosmos_sdk_proto::cosmos::authz::v1beta1::MsgGrant;
+use cosmos_sdk_proto::prost::Message;
+use cosmwasm_std::{Addr, AnyMsg, CosmosMsg, StdResult};
+use cw_multi_test::Executor;
+use treasury::grant::Any;
+use treasury::msg::QueryMsg;
+use xion_mock::XionApp;
+
+fn grant_msgs(
+    app: &XionApp,
+    treasury: &Addr,
+    granter: &Addr,
+    grantee: &Addr,
+    optional_type_urls: &[&str],
+    expires_in: Option<u64>,
+) -> StdResult<Vec<Any>> {
+    app.wrap().query_wasm_smart(
+        treasury,
+        &QueryMsg::GrantMsgs {
+            granter: granter.clone(),
+            grantee: grantee.clone(),
+            optional_type_urls: optional_type_urls.iter().map(|t| t.to_string()).collect(),
+            expires_in,
+        },
+    )
+}
+
+#[test]
+fn test_grant_msgs() {
+    let mut app = setup_app();
+    let admin = app.api().addr_make("admin");
+    let user = app.api().addr_make("user");
+    let session = app.api().addr_make("session");
+    let treasury = setup_treasury(
+        &mut app,
+        &admin,
+        &[(MSG_SEND, false), (MSG_DELEGATE, true)],
+        Some(3600),
+    );
+
+    // optional configs are only included when selected
+    let msgs = grant_msgs(&app, &treasury, &user, &session, &[], None).unwrap();
+    assert_eq!(msgs.len(), 1);
+    assert_eq!(msgs[0].type_url, "/cosmos.authz.v1beta1.MsgGrant");
+    let msg = MsgGrant::decode(msgs[0].value.as_slice()).unwrap();
+    assert_eq!(msg.granter, user.as_str());
+    assert_eq!(msg.grantee, session.as_str());
+    let grant = msg.grant.unwrap();
+    assert_eq!(grant.expiration, None);
+    assert_eq!(
+        grant.authorization.unwrap().value,
+        generic_authorization(MSG_SEND).encode_to_vec()
+    );
+
+    let msgs = grant_msgs(&app, &treasury, &user, &session, &[MSG_DELEGATE], Some(600)).unwrap();
+    assert_eq!(msgs.len(), 2);
+    let expires_at = app.block_info().time.plus_seconds(600).seconds() as i64;
+    for msg in msgs.iter() {
+        let msg = MsgGrant::decode(msg.value.as_slice()).unwrap();
+        assert_eq!(msg.grant.unwrap().expiration.unwrap().seconds, expires_at);
+    }
+
+    grant_msgs(
+        &app,
+        &treasury,
+        &user,
+        &session,
+        &[MSG_SEND, "/unknown"],
+        None,
+    )
+    .unwrap_err();
+
+    // signing the msgs is enough to deploy a fee grant
+    deploy(&mut app, &treasury, &user, &session, None).unwrap_err();
+    for msg in msgs {
+        app.execute(
+            user.clone(),
+            CosmosMsg::Any(AnyMsg {
+                type_url: msg.type_url,
+                value: msg.value,
+            }),
+        )
+        .unwrap();
+    }
+    deploy(&mut app, &treasury, &user, &session, None).unwrap();
+}
@@ -0,0 +1,51 @@
+use crate::tests::test_helpers::{deploy, grant, setup_app, setup_treasury, MSG_SEND};
+use cosmwasm_std::{coins, Addr};
+use treasury::grant::allowance::AllowanceReport;
+use treasury::msg::QueryMsg;
+use xion_mock::{load_allowance, XionApp};
+
+fn grantee_allowance(app: &XionApp, treasury: &Addr, grantee: &Addr) -> Option<AllowanceReport> {
+    app.wrap()
+        .query_wasm_smart(
+            treasury,
+            &QueryMsg::GranteeAllowance {
+                grantee: grantee.clone(),
+            },
+        )
+        .unwrap()
+}
+
+#[test]
+fn test_grantee_allowance() {
+    let mut app = setup_app();
+    let admin = app.api().addr_make("admin");
+    let user = app.api().addr_make("user");
+    let grantee = app.api().addr_make("grantee");
+    let treasury = setup_treasury(&mut app, &admin, &[(MSG_SEND, false)], Some(3600));
+
+    // the chain errors for a grantee without an allowance
+    assert_eq!(grantee_allowance(&app, &treasury, &grantee), None);
+
+    grant(&mut app, &user, &grantee, MSG_SEND, None);
+    deploy(&mut app, &treasury, &user, &grantee, None).unwrap();
+
+    // the report is decoded from the allowance the chain holds for the grantee
+    let deployed = load_allowance(app.storage(), treasury.as_str(), grantee.as_str())
+        .unwrap()
+        .unwrap();
+    let report = grantee_allowance(&app, &treasury, &grantee).unwrap();
+    assert_eq!(
+        report,
+        AllowanceReport {
+            type_url: deployed.type_url,
+            spend_limit: Some(coins(1000, "uxion")),
+            period_can_spend: None,
+            period_reset: None,
+            expiration: Some(app.block_info().time.plus_seconds(3600)),
+        }
+    );
+
+    // another grantee's allowance isn't reported
+    let unknown = app.api().addr_make("unknown");
+    assert_eq!(grantee_allowance(&app, &treasury, &unknown), None);
+}
@@ -0,0 +1,98 @@
+use crate::tests::test_helpers::{deploy, grant, setup_app, setup_treasury, MSG_SEND};
+use cosmwasm_std::Addr;
+use cw_multi_test::Executor;
+use treasury::msg::{ExecuteMsg, QueryMsg};
+use treasury::GranteeRecord;
+
+fn grantees(
+    app: &xion_mock::XionApp,
+    treasury: &Addr,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> Vec<Addr> {
+    let grantees: Vec<(Addr, GranteeRecord)> = app
+        .wrap()
+        .query_wasm_smart(treasury, &QueryMsg::Grantees { start_after, limit })
+        .unwrap();
+    grantees.into_iter().map(|(grantee, _)| grantee).collect()
+}
+
+fn active_grantee_count(app: &xion_mock::XionApp, treasury: &Addr) -> u32 {
+    app.wrap()
+        .query_wasm_smart(treasury, &QueryMsg::ActiveGranteeCount {})
+        .unwrap()
+}
+
+#[test]
+fn test_grantee_registry() {
+    let mut app = setup_app();
+    let admin = app.api().addr_make("admin");
+    let user = app.api().addr_make("user");
+    let treasury = setup_treasury(&mut app, &admin, &[(MSG_SEND, false)], Some(3600));
+
+    let mut sessions: Vec<Addr> = (0..3)
+        .map(|i| app.api().addr_make(&format!("session{i}")))
+        .collect();
+    for session in sessions.iter() {
+        grant(&mut app, &user, session, MSG_SEND, None);
+        deploy(&mut app, &treasury, &user, session, None).unwrap();
+        app.update_block(|block| block.time = block.time.plus_seconds(10));
+    }
+    assert_eq!(active_grantee_count(&app, &treasury), 3);
+
+    let record: GranteeRecord = app
+        .wrap()
+        .query_wasm_smart(
+            &treasury,
+            &QueryMsg::Grantee {
+                grantee: sessions[0].clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(record.granter, user);
+    assert_eq!(record.fee_config_version, 0);
+    assert_eq!(
+        record.expiration,
+        Some(record.deployed_at.plus_seconds(3600))
+    );
+
+    // replacing an allowance doesn't add to the count
+    deploy(&mut app, &treasury, &user, &sessions[1], None).unwrap();
+    assert_eq!(active_grantee_count(&app, &treasury), 3);
+
+    // pages follow the address order
+    sessions.sort();
+    let first = grantees(&app, &treasury, None, Some(2));
+    assert_eq!(first, sessions[..2]);
+    let second = grantees(&app, &treasury, first.last().cloned(), Some(2));
+    assert_eq!(second, sessions[2..]);
+
+    // the redeployed grantee now expires last
+    let by_expiration: Vec<(Addr, GranteeRecord)> = app
+        .wrap()
+        .query_wasm_smart(
+            &treasury,
+            &QueryMsg::GranteesByExpiration {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        by_expiration.last().unwrap().0,
+        app.api().addr_make("session1")
+    );
+
+    let revoked = app.api().addr_make("session0");
+    app.execute_contract(
+        admin.clone(),
+        treasury.clone(),
+        &ExecuteMsg::RevokeAllowance {
+            grantee: revoked.clone(),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(active_grantee_count(&app, &treasury), 2);
+    assert!(!grantees(&app, &treasury, None, None).contains(&revoked));
+}
@@ -0,0 +1,220 @@
+use cosmos_sdk_proto::cosmos::authz::v1beta1::{GenericAuthorization, Grant, MsgGrant, MsgRevoke};
+use cosmos_sdk_proto::cosmos::base::v1beta1::Coin;
+use cosmos_sdk_proto::cosmos::feegrant::v1beta1::BasicAllowance;
+use cosmos_sdk_proto::prost::Message;
+use cosmos_sdk_proto::Any;
+use cosmwasm_std::{Addr, AnyMsg, Binary, CosmosMsg, Empty, Timestamp};
+use cw_multi_test::error::AnyResult;
+use cw_multi_test::{AppResponse, Contract, ContractWrapper, Executor};
+use serde_json::{json, Value};
+use treasury::msg::{ExecuteMsg, InstantiateMsg};
+use xion_mock::{load_allowance, XionApp, XionMockQuerier};
+
+pub const MSG_SEND: &str = "/cosmos.bank.v1beta1.MsgSend";
+pub const MSG_DELEGATE: &str = "/cosmos.staking.v1beta1.MsgDelegate";
+
+pub fn assert_error(result: AnyResult<AppResponse>, expected: &str) {
+    assert_eq!(result.unwrap_err().root_cause().to_string(), expected);
+}
+
+pub fn treasury_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        treasury::contract::execute,
+        treasury::contract::instantiate,
+        treasury::contract::query,
+    ))
+}
+
+pub fn setup_app() -> XionApp {
+    XionMockQuerier::default().into_app()
+}
+
+pub fn generic_authorization(msg: &str) -> GenericAuthorization {
+    GenericAuthorization {
+        msg: msg.to_string(),
+    }
+}
+
+/// A grant config for a generic authorization of `msg`, in its JSON form.
+pub fn grant_config(msg: &str, optional: bool) -> Value {
+    json!({
+        "description": format!("grant {msg}"),
+        "authorization": {
+            "type_url": "/cosmos.authz.v1beta1.GenericAuthorization",
+            "value": Binary::from(generic_authorization(msg).encode_to_vec()),
+        },
+        "optional": optional,
+    })
+}
+
+/// A fee config deploying a basic allowance of `amount` uxion.
+pub fn fee_config(amount: u128, expiration: Option<u32>) -> Value {
+    let allowance = BasicAllowance {
+        spend_limit: vec![Coin {
+            denom: "uxion".to_string(),
+            amount: amount.to_string(),
+        }],
+        expiration: None,
+    };
+    json!({
+        "description": "gas",
+        "allowance": {
+            "type_url": "/cosmos.feegrant.v1beta1.BasicAllowance",
+            "value": Binary::from(allowance.encode_to_vec()),
+        },
+        "expiration": expiration,
+    })
+}
+
+/// Instantiates a treasury with a grant config for each `(msg, optional)`
+/// pair and a 1000 uxion allowance expiring after `expiration` seconds.
+pub fn setup_treasury(
+    app: &mut XionApp,
+    admin: &Addr,
+    grant_configs: &[(&str, bool)],
+    expiration: Option<u32>,
+) -> Addr {
+    let code_id = app.store_code(treasury_contract());
+    let instantiate_msg: InstantiateMsg = serde_json::from_value(json!({
+        "admin": admin,
+        "type_urls": grant_configs.iter().map(|(msg, _)| msg).collect::<Vec<_>>(),
+        "grant_configs": grant_configs
+            .iter()
+            .map(|(msg, optional)| grant_config(msg, *optional))
+            .collect::<Vec<_>>(),
+        "fee_config": fee_config(1000, expiration),
+        "params": {
+            "redirect_url": "https://example.com",
+            "icon_url": "https://example.com/icon.png",
+            "metadata": "{}",
+        },
+    }))
+    .unwrap();
+
+    app.instantiate_contract(
+        code_id,
+        admin.clone(),
+        &instantiate_msg,
+        &[],
+        "treasury",
+        None,
+    )
+    .unwrap()
+}
+
+/// Adds or replaces a grant config, given in its JSON form.
+pub fn update_grant_config(
+    app: &mut XionApp,
+    treasury: &Addr,
+    admin: &Addr,
+    msg_type_url: &str,
+    grant_config: Value,
+) {
+    let msg: ExecuteMsg = serde_json::from_value(json!({
+        "update_grant_config": {
+            "msg_type_url": msg_type_url,
+            "grant_config": grant_config,
+        },
+    }))
+    .unwrap();
+    app.execute_contract(admin.clone(), treasury.clone(), &msg, &[])
+        .unwrap();
+}
+
+/// Grants `grantee` a generic authorization of `msg` from `granter`.
+pub fn grant(
+    app: &mut XionApp,
+    granter: &Addr,
+    grantee: &Addr,
+    msg: &str,
+    expiration: Option<Timestamp>,
+) {
+    let authorization = Any {
+        type_url: "/cosmos.authz.v1beta1.GenericAuthorization".to_string(),
+        value: generic_authorization(msg).encode_to_vec(),
+    };
+    grant_authorization(app, granter, grantee, authorization, expiration);
+}
+
+pub fn grant_authorization(
+    app: &mut XionApp,
+    granter: &Addr,
+    grantee: &Addr,
+    authorization: Any,
+    expiration: Option<Timestamp>,
+) {
+    let grant = MsgGrant {
+        granter: granter.to_string(),
+        grantee: grantee.to_string(),
+        grant: Some(Grant {
+            authorization: Some(authorization),
+            expiration: expiration.map(|expiration| cosmos_sdk_proto::Timestamp {
+                seconds: expiration.seconds() as i64,
+                nanos: 0,
+            }),
+        }),
+    };
+    app.execute(
+        granter.clone(),
+        CosmosMsg::Any(AnyMsg {
+            type_url: "/cosmos.authz.v1beta1.MsgGrant".to_string(),
+            value: grant.encode_to_vec().into(),
+        }),
+    )
+    .unwrap();
+}
+
+pub fn revoke(app: &mut XionApp, granter: &Addr, grantee: &Addr, msg: &str) {
+    let revoke = MsgRevoke {
+        granter: granter.to_string(),
+        grantee: grantee.to_string(),
+        msg_type_url: msg.to_string(),
+    };
+    app.execute(
+        granter.clone(),
+        CosmosMsg::Any(AnyMsg {
+            type_url: "/cosmos.authz.v1beta1.MsgRevoke".to_string(),
+            value: revoke.encode_to_vec().into(),
+        }),
+    )
+    .unwrap();
+}
+
+/// Deploys a fee grant to `grantee`, sent by the grantee.
+pub fn deploy(
+    app: &mut XionApp,
+    treasury: &Addr,
+    granter: &Addr,
+    grantee: &Addr,
+    bundle: Option<&str>,
+) -> AnyResult<AppResponse> {
+    app.execute_contract(
+        grantee.clone(),
+        treasury.clone(),
+        &ExecuteMsg::DeployFeeGrant {
+            authz_granter: granter.clone(),
+            authz_grantee: grantee.clone(),
+            voucher: None,
+            bundle: bundle.map(String::from),
+        },
+        &[],
+    )
+}
+
+/// Whether the treasury's fee allowance to `grantee` exists on the chain.
+pub fn has_allowance(app: &XionApp, treasury: &Addr, grantee: &Addr) -> bool {
+    load_allowance(app.storage(), treasury.as_str(), grantee.as_str())
+        .unwrap()
+        .is_some()
+}
+
+/// The value of the first attribute named `key` in a wasm event of type `ty`.
+pub fn event_attribute(response: &AppResponse, ty: &str, key: &str) -> Option<String> {
+    response
+        .events
+        .iter()
+        .filter(|event| event.ty == format!("wasm-{ty}"))
+        .flat_map(|event| event.attributes.iter())
+        .find(|attr| attr.key == key)
+        .map(|attr| attr.value.clone())
+}
@@ -0,0 +1,148 @@
+use crate::tests::test_helpers::{
+    deploy, event_attribute, grant, grant_authorization, has_allowance, revoke, setup_app,
+    setup_treasury, update_grant_config, MSG_SEND,
+};
+use cosmos_sdk_proto::cosmos::bank::v1beta1::SendAuthorization;
+use cosmos_sdk_proto::prost::Message;
+use cosmos_sdk_proto::Any;
+use cosmwasm_std::{coin, coins, Binary};
+use cw_multi_test::{BankSudo, Executor, SudoMsg};
+use serde_json::json;
+use treasury::msg::{ExecuteMsg, QueryMsg};
+
+const MSG_EXECUTE_CONTRACT: &str = "/cosmwasm.wasm.v1.MsgExecuteContract";
+const CONTRACT_EXECUTION_AUTHORIZATION: &str = "/cosmwasm.wasm.v1.ContractExecutionAuthorization";
+
+#[test]
+fn test_prune_expired() {
+    let mut app = setup_app();
+    let admin = app.api().addr_make("admin");
+    let user = app.api().addr_make("user");
+    let pruner = app.api().addr_make("pruner");
+    let treasury = setup_treasury(&mut app, &admin, &[(MSG_SEND, false)], Some(100));
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: treasury.to_string(),
+        amount: coins(1000, "uxion"),
+    }))
+    .unwrap();
+    app.execute_contract(
+        admin.clone(),
+        treasury.clone(),
+        &ExecuteMsg::UpdatePruneBounty {
+            bounty: Some(coin(10, "uxion")),
+        },
+        &[],
+    )
+    .unwrap();
+    // an optional config whose grants the treasury may fail to decode
+    update_grant_config(
+        &mut app,
+        &treasury,
+        &admin,
+        MSG_EXECUTE_CONTRACT,
+        json!({
+            "description": "execute contracts",
+            "authorization": {
+                "type_url": CONTRACT_EXECUTION_AUTHORIZATION,
+                "value": Binary::default(),
+            },
+            "optional": true,
+        }),
+    );
+    app.execute_contract(
+        admin.clone(),
+        treasury.clone(),
+        &ExecuteMsg::UpdateGrantBundle {
+            name: "contracts".to_string(),
+            msg_type_urls: Some(vec![MSG_EXECUTE_CONTRACT.to_string()]),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let expiring = app.api().addr_make("expiring");
+    grant(&mut app, &user, &expiring, MSG_SEND, None);
+    deploy(&mut app, &treasury, &user, &expiring, None).unwrap();
+    app.update_block(|block| block.time = block.time.plus_seconds(50));
+
+    let [revoked, mismatched, undecodable, valid] =
+        ["revoked", "mismatched", "undecodable", "valid"].map(|name| app.api().addr_make(name));
+    for grantee in [&revoked, &mismatched, &valid] {
+        grant(&mut app, &user, grantee, MSG_SEND, None);
+        deploy(&mut app, &treasury, &user, grantee, None).unwrap();
+    }
+    revoke(&mut app, &user, &revoked, MSG_SEND);
+
+    grant(&mut app, &user, &undecodable, MSG_SEND, None);
+    let contract_execution = |value: Vec<u8>| Any {
+        type_url: CONTRACT_EXECUTION_AUTHORIZATION.to_string(),
+        value,
+    };
+    grant_authorization(
+        &mut app,
+        &user,
+        &undecodable,
+        contract_execution(vec![]),
+        None,
+    );
+    deploy(&mut app, &treasury, &user, &undecodable, Some("contracts")).unwrap();
+    grant_authorization(
+        &mut app,
+        &user,
+        &undecodable,
+        contract_execution(vec![0xff]),
+        None,
+    );
+
+    // replaced by a grant broader than the config, which is still held
+    let send_authorization = SendAuthorization {
+        spend_limit: vec![],
+        allow_list: vec![],
+    };
+    grant_authorization(
+        &mut app,
+        &user,
+        &mismatched,
+        Any {
+            type_url: "/cosmos.bank.v1beta1.SendAuthorization".to_string(),
+            value: send_authorization.encode_to_vec(),
+        },
+        None,
+    );
+
+    app.update_block(|block| block.time = block.time.plus_seconds(60));
+    let res = app
+        .execute_contract(
+            pruner.clone(),
+            treasury.clone(),
+            &ExecuteMsg::PruneExpired { limit: None },
+            &[],
+        )
+        .unwrap();
+
+    // a grant the treasury can't check is skipped rather than failing the batch
+    assert_eq!(
+        event_attribute(&res, "pruned_fee_grants", "skipped"),
+        Some(undecodable.to_string())
+    );
+    assert_eq!(
+        event_attribute(&res, "pruned_fee_grants", "count"),
+        Some("2".to_string())
+    );
+    assert!(!has_allowance(&app, &treasury, &expiring));
+    assert!(!has_allowance(&app, &treasury, &revoked));
+    assert!(has_allowance(&app, &treasury, &mismatched));
+    assert!(has_allowance(&app, &treasury, &undecodable));
+    assert!(has_allowance(&app, &treasury, &valid));
+    let active: u32 = app
+        .wrap()
+        .query_wasm_smart(&treasury, &QueryMsg::ActiveGranteeCount {})
+        .unwrap();
+    assert_eq!(active, 3);
+
+    // only the expired allowance earns the bounty
+    assert_eq!(
+        app.wrap().query_balance(&pruner, "uxion").unwrap(),
+        coin(10, "uxion")
+    );
+}
@@ -0,0 +1,192 @@
+use crate::tests::test_helpers::{
+    deploy, event_attribute, grant, grant_authorization, grant_config, has_allowance, revoke,
+    setup_app, setup_treasury, update_grant_config, MSG_DELEGATE, MSG_SEND,
+};
+use cosmos_sdk_proto::cosmos::bank::v1beta1::SendAuthorization;
+use cosmos_sdk_proto::prost::Message;
+use cosmos_sdk_proto::Any;
+use cosmwasm_std::{Addr, Binary};
+use cw_multi_test::{AppResponse, Executor};
+use serde_json::json;
+use treasury::msg::ExecuteMsg;
+use xion_mock::XionApp;
+
+const MSG_EXECUTE_CONTRACT: &str = "/cosmwasm.wasm.v1.MsgExecuteContract";
+const CONTRACT_EXECUTION_AUTHORIZATION: &str = "/cosmwasm.wasm.v1.ContractExecutionAuthorization";
+
+fn reconcile(app: &mut XionApp, treasury: &Addr, grantee: &Addr) -> AppResponse {
+    let anyone = app.api().addr_make("anyone");
+    app.execute_contract(
+        anyone,
+        treasury.clone(),
+        &ExecuteMsg::Reconcile {
+            grantee: grantee.clone(),
+        },
+        &[],
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_reconcile() {
+    let mut app = setup_app();
+    let admin = app.api().addr_make("admin");
+    let user = app.api().addr_make("user");
+    let treasury = setup_treasury(&mut app, &admin, &[(MSG_SEND, false)], Some(3600));
+    let [kept, revoked] = ["kept", "revoked"].map(|name| app.api().addr_make(name));
+    for grantee in [&kept, &revoked] {
+        grant(&mut app, &user, grantee, MSG_SEND, None);
+        deploy(&mut app, &treasury, &user, grantee, None).unwrap();
+    }
+
+    // a config required after deployment doesn't apply to existing grantees
+    update_grant_config(
+        &mut app,
+        &treasury,
+        &admin,
+        MSG_DELEGATE,
+        grant_config(MSG_DELEGATE, false),
+    );
+    // nor does a held grant no longer fitting its config
+    let send_authorization = SendAuthorization {
+        spend_limit: vec![],
+        allow_list: vec![],
+    };
+    grant_authorization(
+        &mut app,
+        &user,
+        &kept,
+        Any {
+            type_url: "/cosmos.bank.v1beta1.SendAuthorization".to_string(),
+            value: send_authorization.encode_to_vec(),
+        },
+        None,
+    );
+    let res = reconcile(&mut app, &treasury, &kept);
+    assert_eq!(
+        event_attribute(&res, "reconciled_fee_grant", "result"),
+        Some("valid".to_string())
+    );
+    assert!(has_allowance(&app, &treasury, &kept));
+
+    // a required grant that is gone does
+    revoke(&mut app, &user, &revoked, MSG_SEND);
+    let res = reconcile(&mut app, &treasury, &revoked);
+    assert_eq!(
+        event_attribute(&res, "reconciled_fee_grant", "result"),
+        Some("revoked".to_string())
+    );
+    assert_eq!(
+        event_attribute(&res, "reconciled_fee_grant", "reasons"),
+        Some(format!("missing:{MSG_SEND}"))
+    );
+    assert!(!has_allowance(&app, &treasury, &revoked));
+
+    let res = reconcile(&mut app, &treasury, &revoked);
+    assert_eq!(
+        event_attribute(&res, "reconciled_fee_grant", "result"),
+        Some("not_found".to_string())
+    );
+}
+
+#[test]
+fn test_reconcile_batch_skips_unverifiable() {
+    let mut app = setup_app();
+    let admin = app.api().addr_make("admin");
+    let user = app.api().addr_make("user");
+    let treasury = setup_treasury(&mut app, &admin, &[(MSG_SEND, false)], None);
+    // an optional config whose grants the treasury may fail to decode
+    update_grant_config(
+        &mut app,
+        &treasury,
+        &admin,
+        MSG_EXECUTE_CONTRACT,
+        json!({
+            "description": "execute contracts",
+            "authorization": {
+                "type_url": CONTRACT_EXECUTION_AUTHORIZATION,
+                "value": Binary::default(),
+            },
+            "optional": true,
+        }),
+    );
+    app.execute_contract(
+        admin.clone(),
+        treasury.clone(),
+        &ExecuteMsg::UpdateGrantBundle {
+            name: "contracts".to_string(),
+            msg_type_urls: Some(vec![MSG_EXECUTE_CONTRACT.to_string()]),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let [undecodable, revoked] = ["undecodable", "revoked"].map(|name| app.api().addr_make(name));
+    grant(&mut app, &user, &revoked, MSG_SEND, None);
+    deploy(&mut app, &treasury, &user, &revoked, None).unwrap();
+    revoke(&mut app, &user, &revoked, MSG_SEND);
+
+    let contract_execution = |value: Vec<u8>| Any {
+        type_url: CONTRACT_EXECUTION_AUTHORIZATION.to_string(),
+        value,
+    };
+    grant(&mut app, &user, &undecodable, MSG_SEND, None);
+    grant_authorization(
+        &mut app,
+        &user,
+        &undecodable,
+        contract_execution(vec![]),
+        None,
+    );
+    deploy(&mut app, &treasury, &user, &undecodable, Some("contracts")).unwrap();
+    grant_authorization(
+        &mut app,
+        &user,
+        &undecodable,
+        contract_execution(vec![0xff]),
+        None,
+    );
+
+    let anyone = app.api().addr_make("anyone");
+    let res = app
+        .execute_contract(
+            anyone,
+            treasury.clone(),
+            &ExecuteMsg::ReconcileBatch {
+                grantees: vec![undecodable.clone(), revoked.clone()],
+            },
+            &[],
+        )
+        .unwrap();
+
+    let results: Vec<(String, String)> = res
+        .events
+        .iter()
+        .filter(|event| event.ty == "wasm-reconciled_fee_grant")
+        .map(|event| {
+            let attribute = |key: &str| {
+                event
+                    .attributes
+                    .iter()
+                    .find(|attr| attr.key == key)
+                    .unwrap()
+                    .value
+                    .clone()
+            };
+            (attribute("grantee"), attribute("result"))
+        })
+        .collect();
+    assert_eq!(
+        results,
+        vec![
+            (undecodable.to_string(), "skipped".to_string()),
+            (revoked.to_string(), "revoked".to_string()),
+        ]
+    );
+    assert_eq!(
+        event_attribute(&res, "reconciled_fee_grants", "skipped"),
+        Some(undecodable.to_string())
+    );
+    assert!(has_allowance(&app, &treasury, &undecodable));
+    assert!(!has_allowance(&app, &treasury, &revoked));
+}
@@ -0,0 +1,85 @@
+use crate::tests::test_helpers::{
+    assert_error, deploy, event_attribute, grant, has_allowance, setup_app, setup_treasury,
+    MSG_SEND,
+};
+use cosmwasm_std::Addr;
+use cw_multi_test::Executor;
+use treasury::msg::{ExecuteMsg, QueryMsg};
+use treasury::Role;
+
+#[test]
+fn test_revoke_all_allowances() {
+    let mut app = setup_app();
+    let admin = app.api().addr_make("admin");
+    let guardian = app.api().addr_make("guardian");
+    let user = app.api().addr_make("user");
+    let treasury = setup_treasury(&mut app, &admin, &[(MSG_SEND, false)], None);
+
+    app.execute_contract(
+        admin.clone(),
+        treasury.clone(),
+        &ExecuteMsg::ProposeRole {
+            role: Role::Guardian,
+            member: guardian.clone(),
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        guardian.clone(),
+        treasury.clone(),
+        &ExecuteMsg::AcceptRole {
+            role: Role::Guardian,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let sessions: Vec<Addr> = (0..3)
+        .map(|i| app.api().addr_make(&format!("session{i}")))
+        .collect();
+    for session in sessions.iter() {
+        grant(&mut app, &user, session, MSG_SEND, None);
+        deploy(&mut app, &treasury, &user, session, None).unwrap();
+    }
+
+    let revoke_all = ExecuteMsg::RevokeAllAllowances { limit: Some(2) };
+    let result = app.execute_contract(admin.clone(), treasury.clone(), &revoke_all, &[]);
+    assert_error(result, "treasury is not paused");
+
+    app.execute_contract(
+        guardian.clone(),
+        treasury.clone(),
+        &ExecuteMsg::Pause { unpause_at: None },
+        &[],
+    )
+    .unwrap();
+
+    // a guardian can pause but not revoke
+    let result = app.execute_contract(guardian.clone(), treasury.clone(), &revoke_all, &[]);
+    assert_error(result, "unauthorized");
+
+    let res = app
+        .execute_contract(admin.clone(), treasury.clone(), &revoke_all, &[])
+        .unwrap();
+    assert_eq!(
+        event_attribute(&res, "revoked_all_treasury_allowances", "remaining"),
+        Some("1".to_string())
+    );
+    let res = app
+        .execute_contract(admin.clone(), treasury.clone(), &revoke_all, &[])
+        .unwrap();
+    assert_eq!(
+        event_attribute(&res, "revoked_all_treasury_allowances", "remaining"),
+        Some("0".to_string())
+    );
+
+    for session in sessions.iter() {
+        assert!(!has_allowance(&app, &treasury, session));
+    }
+    let count: u32 = app
+        .wrap()
+        .query_wasm_smart(&treasury, &QueryMsg::ActiveGranteeCount {})
+        .unwrap();
+    assert_eq!(count, 0);
+}
@@ -0,0 +1,79 @@
+use crate::tests::test_helpers::{
+    assert_error, deploy, grant, setup_app, setup_treasury, MSG_SEND,
+};
+use cosmwasm_std::{coin, coins};
+use cw_multi_test::Executor;
+use treasury::msg::{BudgetUsageResponse, ExecuteMsg, QueryMsg};
+use treasury::SpendBudget;
+
+#[test]
+fn test_spend_budget() {
+    let mut app = setup_app();
+    let admin = app.api().addr_make("admin");
+    let user = app.api().addr_make("user");
+    let treasury = setup_treasury(&mut app, &admin, &[(MSG_SEND, false)], None);
+    app.execute_contract(
+        admin.clone(),
+        treasury.clone(),
+        &ExecuteMsg::UpdateSpendBudget {
+            budget: Some(SpendBudget {
+                amount: coins(2500, "uxion"),
+                period: 3600,
+            }),
+        },
+        &[],
+    )
+    .unwrap();
+    let period_start = app.block_info().time;
+    let [a, b, c] = ["a", "b", "c"].map(|name| app.api().addr_make(name));
+    for grantee in [&a, &b, &c] {
+        grant(&mut app, &user, grantee, MSG_SEND, None);
+    }
+
+    // each allowance commits its 1000 uxion spend limit
+    deploy(&mut app, &treasury, &user, &a, None).unwrap();
+    app.update_block(|block| block.time = block.time.plus_seconds(1000));
+    deploy(&mut app, &treasury, &user, &b, None).unwrap();
+    assert_error(
+        deploy(&mut app, &treasury, &user, &c, None),
+        "spend budget exhausted for uxion",
+    );
+    let usage: Option<BudgetUsageResponse> = app
+        .wrap()
+        .query_wasm_smart(&treasury, &QueryMsg::BudgetUsage {})
+        .unwrap();
+    let usage = usage.unwrap();
+    assert_eq!(usage.usage.period_start, period_start);
+    assert_eq!(usage.usage.committed, coins(2000, "uxion"));
+    assert_eq!(usage.remaining, coins(500, "uxion"));
+
+    // the next period starts from nothing, aligned to the first one
+    app.update_block(|block| block.time = block.time.plus_seconds(6000));
+    deploy(&mut app, &treasury, &user, &c, None).unwrap();
+    let usage: Option<BudgetUsageResponse> = app
+        .wrap()
+        .query_wasm_smart(&treasury, &QueryMsg::BudgetUsage {})
+        .unwrap();
+    let usage = usage.unwrap();
+    assert_eq!(usage.usage.period_start, period_start.plus_seconds(3600));
+    assert_eq!(usage.remaining, vec![coin(1500, "uxion")]);
+
+    // a denom missing from the budget has none to spend
+    app.execute_contract(
+        admin.clone(),
+        treasury.clone(),
+        &ExecuteMsg::UpdateSpendBudget {
+            budget: Some(SpendBudget {
+                amount: coins(2500, "uatom"),
+                period: 0,
+            }),
+        },
+        &[],
+    )
+    .unwrap();
+    app.update_block(|block| block.time = block.time.plus_seconds(1));
+    assert_error(
+        deploy(&mut app, &treasury, &user, &a, None),
+        "spend budget exhausted for uxion",
+    );
+}
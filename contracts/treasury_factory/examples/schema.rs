@@ -0,0 +1,11 @@
+use cosmwasm_schema::write_api;
+use treasury_factory::msg::*;
+
+fn main() {
+    write_api! {
+        instantiate: InstantiateMsg,
+        query: QueryMsg,
+        execute: ExecuteMsg,
+        migrate: MigrateMsg,
+    };
+}
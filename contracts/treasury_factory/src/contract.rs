@@ -0,0 +1,76 @@
+use crate::error::ContractResult;
+use crate::migrations::MIGRATIONS;
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use crate::{execute, query, CONTRACT_NAME, CONTRACT_VERSION};
+use cosmwasm_std::{
+    entry_point, to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+};
+
+#[entry_point]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> ContractResult<Response> {
+    cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    execute::init(deps, info, msg.admin, msg.treasury_code_id)
+}
+
+#[entry_point]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> ContractResult<Response> {
+    match msg {
+        ExecuteMsg::CreateTreasury {
+            app_id,
+            config,
+            params,
+            admin,
+        } => execute::create_treasury(deps, env, info, app_id, config, params, admin),
+        ExecuteMsg::UpdateTemplate { name, template } => {
+            execute::update_template(deps, info, name, template)
+        }
+        ExecuteMsg::UpdateTreasuryCodeId { code_id } => {
+            execute::update_treasury_code_id(deps, info, code_id)
+        }
+        ExecuteMsg::UpdateAdmin { admin } => execute::update_admin(deps, info, admin),
+    }
+}
+
+#[entry_point]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Admin {} => to_json_binary(&query::admin(deps.storage)?),
+        QueryMsg::TreasuryCodeId {} => to_json_binary(&query::treasury_code_id(deps.storage)?),
+        QueryMsg::Templates {} => to_json_binary(&query::templates(deps.storage)?),
+        QueryMsg::Treasury { owner, app_id } => {
+            to_json_binary(&query::treasury(deps.storage, owner, app_id)?)
+        }
+        QueryMsg::TreasuriesByOwner {
+            owner,
+            start_after,
+            limit,
+        } => to_json_binary(&query::treasuries_by_owner(
+            deps.storage,
+            owner,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::Treasuries { start_after, limit } => {
+            to_json_binary(&query::treasuries(deps.storage, start_after, limit)?)
+        }
+        QueryMsg::PredictAddress { owner, app_id } => {
+            to_json_binary(&query::predict(deps, &env, owner, app_id)?)
+        }
+    }
+}
+
+#[entry_point]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> ContractResult<Response> {
+    let report = migration::migrate(deps.storage, CONTRACT_NAME, CONTRACT_VERSION, MIGRATIONS)?;
+    Ok(report.into_response())
+}
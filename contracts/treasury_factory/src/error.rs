@@ -0,0 +1,24 @@
+#[allow(dead_code)]
+#[derive(Debug, thiserror::Error)]
+pub enum ContractError {
+    #[error(transparent)]
+    Std(#[from] cosmwasm_std::StdError),
+
+    #[error(transparent)]
+    Instantiate2Address(#[from] cosmwasm_std::Instantiate2AddressError),
+
+    #[error(transparent)]
+    Migration(#[from] migration::MigrationError),
+
+    #[error("unauthorized")]
+    Unauthorized,
+
+    #[error("template {name} not found")]
+    TemplateNotFound { name: String },
+
+    #[error("treasury for app {app_id} already exists")]
+    TreasuryExists { app_id: String },
+}
+
+#[allow(dead_code)]
+pub type ContractResult<T> = Result<T, ContractError>;
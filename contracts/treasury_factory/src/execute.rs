@@ -0,0 +1,326 @@
+use crate::error::ContractError::{TemplateNotFound, TreasuryExists, Unauthorized};
+use crate::error::ContractResult;
+use crate::msg::TreasuryConfig;
+use crate::state::{
+    TreasuryRecord, TreasuryTemplate, ADMIN, TEMPLATES, TREASURIES, TREASURY_CODE_ID,
+};
+use cosmwasm_std::{
+    instantiate2_address, to_json_binary, Addr, Binary, Deps, DepsMut, Env, Event, MessageInfo,
+    Response, WasmMsg,
+};
+use sha2::{Digest, Sha256};
+use treasury::msg::InstantiateMsg as TreasuryInstantiateMsg;
+use treasury::Params;
+
+pub fn init(
+    deps: DepsMut,
+    info: MessageInfo,
+    admin: Option<Addr>,
+    treasury_code_id: u64,
+) -> ContractResult<Response> {
+    let admin = match admin {
+        Some(admin) => deps.api.addr_validate(admin.as_str())?,
+        None => info.sender,
+    };
+    ADMIN.save(deps.storage, &admin)?;
+    TREASURY_CODE_ID.save(deps.storage, &treasury_code_id)?;
+
+    Ok(
+        Response::new().add_event(Event::new("create_treasury_factory").add_attributes(vec![
+            ("admin", admin.into_string()),
+            ("treasury_code_id", treasury_code_id.to_string()),
+        ])),
+    )
+}
+
+fn assert_admin(deps: Deps, sender: &Addr) -> ContractResult<()> {
+    if ADMIN.load(deps.storage)? != *sender {
+        return Err(Unauthorized);
+    }
+    Ok(())
+}
+
+/// The instantiate2 salt of the treasury for an owner's app.
+pub fn salt(owner: &Addr, app_id: &str) -> Binary {
+    let mut hasher = Sha256::new();
+    hasher.update(owner.as_bytes());
+    hasher.update([0]);
+    hasher.update(app_id.as_bytes());
+    Binary::from(hasher.finalize().to_vec())
+}
+
+pub fn predict_address(
+    deps: Deps,
+    env: &Env,
+    code_id: u64,
+    owner: &Addr,
+    app_id: &str,
+) -> ContractResult<Addr> {
+    let checksum = deps.querier.query_wasm_code_info(code_id)?.checksum;
+    let creator = deps.api.addr_canonicalize(env.contract.address.as_str())?;
+    let address = instantiate2_address(checksum.as_slice(), &creator, &salt(owner, app_id))?;
+    Ok(deps.api.addr_humanize(&address)?)
+}
+
+pub fn create_treasury(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    app_id: String,
+    config: TreasuryConfig,
+    params: Params,
+    admin: Option<Addr>,
+) -> ContractResult<Response> {
+    let owner = info.sender;
+    if TREASURIES.has(deps.storage, (&owner, &app_id)) {
+        return Err(TreasuryExists { app_id });
+    }
+
+    let template = match config {
+        TreasuryConfig::Template { name } => TEMPLATES
+            .may_load(deps.storage, name.clone())?
+            .ok_or(TemplateNotFound { name })?,
+        TreasuryConfig::Custom(template) => template,
+    };
+    let admin = match admin {
+        Some(admin) => deps.api.addr_validate(admin.as_str())?,
+        None => owner.clone(),
+    };
+
+    let code_id = TREASURY_CODE_ID.load(deps.storage)?;
+    let address = predict_address(deps.as_ref(), &env, code_id, &owner, &app_id)?;
+    TREASURIES.save(
+        deps.storage,
+        (&owner, &app_id),
+        &TreasuryRecord {
+            address: address.clone(),
+            code_id,
+            created_at: env.block.time,
+        },
+    )?;
+
+    // treasuries migrate themselves, so each is its own wasmd admin
+    let instantiate_msg = WasmMsg::Instantiate2 {
+        admin: Some(address.to_string()),
+        code_id,
+        label: format!("treasury {app_id}"),
+        msg: to_json_binary(&TreasuryInstantiateMsg {
+            admin: Some(admin.clone()),
+            type_urls: template.type_urls,
+            grant_configs: template.grant_configs,
+            fee_config: template.fee_config,
+            params,
+        })?,
+        funds: info.funds,
+        salt: salt(&owner, &app_id),
+    };
+
+    Ok(Response::new().add_message(instantiate_msg).add_event(
+        Event::new("created_treasury").add_attributes(vec![
+            ("owner", owner.into_string()),
+            ("app_id", app_id),
+            ("address", address.into_string()),
+            ("admin", admin.into_string()),
+        ]),
+    ))
+}
+
+pub fn update_template(
+    deps: DepsMut,
+    info: MessageInfo,
+    name: String,
+    template: Option<TreasuryTemplate>,
+) -> ContractResult<Response> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+
+    match &template {
+        None => TEMPLATES.remove(deps.storage, name.clone()),
+        Some(template) => TEMPLATES.save(deps.storage, name.clone(), template)?,
+    }
+
+    Ok(Response::new().add_event(
+        Event::new("updated_treasury_template")
+            .add_attribute("name", name)
+            .add_attribute("removed", template.is_none().to_string()),
+    ))
+}
+
+pub fn update_treasury_code_id(
+    deps: DepsMut,
+    info: MessageInfo,
+    code_id: u64,
+) -> ContractResult<Response> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+
+    TREASURY_CODE_ID.save(deps.storage, &code_id)?;
+
+    Ok(Response::new().add_event(
+        Event::new("updated_treasury_code_id").add_attribute("code_id", code_id.to_string()),
+    ))
+}
+
+pub fn update_admin(deps: DepsMut, info: MessageInfo, admin: Addr) -> ContractResult<Response> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+
+    let admin = deps.api.addr_validate(admin.as_str())?;
+    ADMIN.save(deps.storage, &admin)?;
+
+    Ok(Response::new()
+        .add_event(Event::new("updated_factory_admin").add_attribute("admin", admin.into_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::ContractError;
+    use crate::execute::{
+        create_treasury, init, predict_address, update_admin, update_template,
+        update_treasury_code_id,
+    };
+    use crate::msg::TreasuryConfig;
+    use crate::state::{TreasuryTemplate, TREASURIES};
+    use cosmwasm_std::testing::{message_info, mock_dependencies, mock_env};
+    use cosmwasm_std::{
+        from_json, to_json_binary, Checksum, CodeInfoResponse, ContractResult, CosmosMsg,
+        SystemResult, WasmMsg, WasmQuery,
+    };
+    use treasury::Params;
+
+    fn params() -> Params {
+        Params {
+            redirect_url: "https://example.com".to_string(),
+            icon_url: "https://example.com/icon.png".to_string(),
+            metadata: "{}".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_create_treasury_at_predicted_address() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let creator = deps.api.addr_make("creator");
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::CodeInfo { code_id } => SystemResult::Ok(ContractResult::Ok(
+                to_json_binary(&CodeInfoResponse::new(
+                    *code_id,
+                    creator.clone(),
+                    Checksum::generate(b"treasury"),
+                ))
+                .unwrap(),
+            )),
+            _ => panic!("unexpected query"),
+        });
+
+        let admin = deps.api.addr_make("admin");
+        let owner = deps.api.addr_make("owner");
+        init(deps.as_mut(), message_info(&admin, &[]), None, 7).unwrap();
+        let template: TreasuryTemplate = from_json(
+            r#"{"type_urls":[],"grant_configs":[],"fee_config":{"description":"","allowance":null,"expiration":null}}"#,
+        )
+        .unwrap();
+        update_template(
+            deps.as_mut(),
+            message_info(&admin, &[]),
+            "basic".to_string(),
+            Some(template),
+        )
+        .unwrap();
+
+        let predicted = predict_address(deps.as_ref(), &env, 7, &owner, "app").unwrap();
+        let params = params();
+        let res = create_treasury(
+            deps.as_mut(),
+            env.clone(),
+            message_info(&owner, &[]),
+            "app".to_string(),
+            TreasuryConfig::Template {
+                name: "basic".to_string(),
+            },
+            params.clone(),
+            None,
+        )
+        .unwrap();
+
+        match &res.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Instantiate2 { admin, code_id, .. }) => {
+                assert_eq!(*code_id, 7);
+                assert_eq!(admin.as_deref(), Some(predicted.as_str()));
+            }
+            _ => panic!("unexpected message"),
+        }
+        assert_eq!(
+            TREASURIES
+                .load(&deps.storage, (&owner, "app"))
+                .unwrap()
+                .address,
+            predicted
+        );
+
+        // the app id can't be reused by the same owner
+        assert!(create_treasury(
+            deps.as_mut(),
+            env,
+            message_info(&owner, &[]),
+            "app".to_string(),
+            TreasuryConfig::Template {
+                name: "basic".to_string(),
+            },
+            params,
+            None,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_admin_only() {
+        let mut deps = mock_dependencies();
+        let admin = deps.api.addr_make("admin");
+        let other = deps.api.addr_make("other");
+        init(deps.as_mut(), message_info(&admin, &[]), None, 7).unwrap();
+
+        let err = update_template(
+            deps.as_mut(),
+            message_info(&other, &[]),
+            "basic".to_string(),
+            None,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized));
+        let err = update_treasury_code_id(deps.as_mut(), message_info(&other, &[]), 8).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized));
+        let err =
+            update_admin(deps.as_mut(), message_info(&other, &[]), other.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized));
+
+        // the new admin takes over from the old
+        update_admin(deps.as_mut(), message_info(&admin, &[]), other.clone()).unwrap();
+        let err = update_treasury_code_id(deps.as_mut(), message_info(&admin, &[]), 8).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized));
+        update_treasury_code_id(deps.as_mut(), message_info(&other, &[]), 8).unwrap();
+    }
+
+    #[test]
+    fn test_create_treasury_unknown_template() {
+        let mut deps = mock_dependencies();
+        let admin = deps.api.addr_make("admin");
+        let owner = deps.api.addr_make("owner");
+        init(deps.as_mut(), message_info(&admin, &[]), None, 7).unwrap();
+
+        let err = create_treasury(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&owner, &[]),
+            "app".to_string(),
+            TreasuryConfig::Template {
+                name: "missing".to_string(),
+            },
+            params(),
+            None,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::TemplateNotFound { name } if name == "missing"
+        ));
+        assert!(!TREASURIES.has(&deps.storage, (&owner, "app")));
+    }
+}
@@ -0,0 +1,13 @@
+extern crate core;
+
+#[cfg(not(feature = "library"))]
+pub mod contract;
+mod error;
+mod execute;
+mod migrations;
+pub mod msg;
+mod query;
+mod state;
+
+pub const CONTRACT_NAME: &str = "treasury-factory";
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -0,0 +1,6 @@
+use migration::Migration;
+
+/// State transforms by the version that introduced them, see
+/// [`migration::migrate`].
+#[allow(dead_code)]
+pub const MIGRATIONS: &[Migration] = &[];
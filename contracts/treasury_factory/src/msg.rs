@@ -0,0 +1,80 @@
+use crate::state::{TreasuryRecord, TreasuryTemplate};
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::Addr;
+use treasury::Params;
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    /// Defaults to the sender
+    pub admin: Option<Addr>,
+    pub treasury_code_id: u64,
+}
+
+/// Where a new treasury's grant and fee configs come from.
+#[cw_serde]
+pub enum TreasuryConfig {
+    Template { name: String },
+    Custom(TreasuryTemplate),
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Instantiate a treasury for the sender's app, at the address returned
+    /// by `PredictAddress`
+    CreateTreasury {
+        app_id: String,
+        config: TreasuryConfig,
+        params: Params,
+        /// The treasury admin, defaults to the sender
+        admin: Option<Addr>,
+    },
+    UpdateTemplate {
+        name: String,
+        template: Option<TreasuryTemplate>,
+    },
+    UpdateTreasuryCodeId {
+        code_id: u64,
+    },
+    UpdateAdmin {
+        admin: Addr,
+    },
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(Addr)]
+    Admin {},
+
+    #[returns(u64)]
+    TreasuryCodeId {},
+
+    #[returns(Vec<(String, TreasuryTemplate)>)]
+    Templates {},
+
+    #[returns(Option<TreasuryRecord>)]
+    Treasury { owner: Addr, app_id: String },
+
+    /// Query the treasuries created by an owner, ordered by app id
+    #[returns(Vec<(String, TreasuryRecord)>)]
+    TreasuriesByOwner {
+        owner: Addr,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Query all treasuries, ordered by owner and app id
+    #[returns(Vec<(Addr, String, TreasuryRecord)>)]
+    Treasuries {
+        start_after: Option<(Addr, String)>,
+        limit: Option<u32>,
+    },
+
+    /// Query the address a treasury for the owner's app is or would be
+    /// instantiated at with the current treasury code
+    #[returns(Addr)]
+    PredictAddress { owner: Addr, app_id: String },
+}
+
+#[cw_serde]
+pub struct MigrateMsg {}
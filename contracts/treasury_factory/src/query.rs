@@ -0,0 +1,87 @@
+use crate::execute::predict_address;
+use crate::state::{
+    TreasuryRecord, TreasuryTemplate, ADMIN, TEMPLATES, TREASURIES, TREASURY_CODE_ID,
+};
+use cosmwasm_std::{Addr, Deps, Env, Order, StdError, StdResult, Storage};
+use cw_storage_plus::Bound;
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+#[allow(dead_code)]
+pub fn admin(store: &dyn Storage) -> StdResult<Addr> {
+    ADMIN.load(store)
+}
+
+#[allow(dead_code)]
+pub fn treasury_code_id(store: &dyn Storage) -> StdResult<u64> {
+    TREASURY_CODE_ID.load(store)
+}
+
+#[allow(dead_code)]
+pub fn templates(store: &dyn Storage) -> StdResult<Vec<(String, TreasuryTemplate)>> {
+    TEMPLATES
+        .range(store, None, None, Order::Ascending)
+        .collect()
+}
+
+#[allow(dead_code)]
+pub fn treasury(
+    store: &dyn Storage,
+    owner: Addr,
+    app_id: String,
+) -> StdResult<Option<TreasuryRecord>> {
+    TREASURIES.may_load(store, (&owner, &app_id))
+}
+
+#[allow(dead_code)]
+pub fn treasuries_by_owner(
+    store: &dyn Storage,
+    owner: Addr,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(String, TreasuryRecord)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    TREASURIES
+        .prefix(&owner)
+        .range(
+            store,
+            start_after.as_deref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect()
+}
+
+#[allow(dead_code)]
+pub fn treasuries(
+    store: &dyn Storage,
+    start_after: Option<(Addr, String)>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(Addr, String, TreasuryRecord)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    TREASURIES
+        .range(
+            store,
+            start_after
+                .as_ref()
+                .map(|(owner, app_id)| Bound::exclusive((owner, app_id.as_str()))),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| item.map(|((owner, app_id), record)| (owner, app_id, record)))
+        .collect()
+}
+
+#[allow(dead_code)]
+pub fn predict(deps: Deps, env: &Env, owner: Addr, app_id: String) -> StdResult<Addr> {
+    // an existing treasury keeps the address it was created at
+    if let Some(record) = TREASURIES.may_load(deps.storage, (&owner, &app_id))? {
+        return Ok(record.address);
+    }
+    let code_id = TREASURY_CODE_ID.load(deps.storage)?;
+    predict_address(deps, env, code_id, &owner, &app_id)
+        .map_err(|err| StdError::generic_err(err.to_string()))
+}
@@ -0,0 +1,30 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Timestamp};
+use cw_storage_plus::{Item, Map};
+use treasury::grant::{FeeConfig, GrantConfig};
+
+pub const ADMIN: Item<Addr> = Item::new("admin");
+
+// code id of the treasury contract new treasuries are instantiated from
+pub const TREASURY_CODE_ID: Item<u64> = Item::new("treasury_code_id");
+
+/// Grant and fee configs shared by treasuries created from it.
+#[cw_serde]
+pub struct TreasuryTemplate {
+    pub type_urls: Vec<String>,
+    pub grant_configs: Vec<GrantConfig>,
+    pub fee_config: FeeConfig,
+}
+
+// template name to template
+pub const TEMPLATES: Map<String, TreasuryTemplate> = Map::new("templates");
+
+#[cw_serde]
+pub struct TreasuryRecord {
+    pub address: Addr,
+    pub code_id: u64,
+    pub created_at: Timestamp,
+}
+
+// (owner, app id) to the treasury created for them
+pub const TREASURIES: Map<(&Addr, &str), TreasuryRecord> = Map::new("treasuries");
@@ -1,20 +1,28 @@
 use crate::error::ContractError;
 use crate::error::ContractResult;
-use crate::msg::InstantiateMsg;
+use crate::migrations::MIGRATIONS;
 use crate::msg::{ExecuteMsg, QueryMsg};
-use crate::state::USER_MAP;
+use crate::msg::{InstantiateMsg, MigrateMsg};
+use crate::state::{USER_COUNT, USER_MAP};
+use crate::{CONTRACT_NAME, CONTRACT_VERSION};
 use cosmwasm_std::{
     entry_point, to_json_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response,
     StdResult,
 };
+use cw_storage_plus::Bound;
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
 
 #[entry_point]
 pub fn instantiate(
-    _deps: DepsMut,
+    deps: DepsMut,
     _env: Env,
     info: MessageInfo,
     _msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
+    cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    USER_COUNT.save(deps.storage, &0)?;
     Ok(Response::new()
         .add_attribute("method", "instantiate")
         .add_attribute("owner", info.sender))
@@ -31,6 +39,9 @@ pub fn execute(
             // validate JSON
             serde_json::from_str::<serde_json::Value>(&value)?;
 
+            if !USER_MAP.has(deps.storage, info.sender.clone()) {
+                USER_COUNT.update(deps.storage, |count| StdResult::Ok(count + 1))?;
+            }
             USER_MAP.save(deps.storage, info.sender, &value)?;
             Ok(Response::default())
         }
@@ -44,6 +55,7 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             let value = USER_MAP.load(deps.storage, address)?;
             to_json_binary(&value)
         }
+        #[allow(deprecated)]
         QueryMsg::GetUsers {} => {
             let mut addrs: Vec<Addr> = Vec::new();
             for addr in USER_MAP.keys(deps.storage, None, None, Order::Ascending) {
@@ -51,6 +63,7 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             }
             to_json_binary(&addrs)
         }
+        #[allow(deprecated)]
         QueryMsg::GetMap {} => {
             let mut response: Vec<(Addr, String)> = Vec::new();
             for item in USER_MAP.range(deps.storage, None, None, Order::Ascending) {
@@ -59,5 +72,133 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             }
             to_json_binary(&response)
         }
+        QueryMsg::Users {
+            start_after,
+            limit,
+            order,
+        } => to_json_binary(&query_users(deps, start_after, limit, order)?),
+        QueryMsg::Map {
+            start_after,
+            limit,
+            order,
+        } => to_json_binary(&query_map(deps, start_after, limit, order)?),
+        QueryMsg::Count {} => to_json_binary(&USER_COUNT.load(deps.storage)?),
+    }
+}
+
+/// Range bounds continuing after `start_after` in the direction of `order`
+fn page_bounds(
+    start_after: Option<Addr>,
+    order: Order,
+) -> (Option<Bound<'static, Addr>>, Option<Bound<'static, Addr>>) {
+    let bound = start_after.map(Bound::exclusive);
+    match order {
+        Order::Ascending => (bound, None),
+        Order::Descending => (None, bound),
+    }
+}
+
+pub fn query_users(
+    deps: Deps,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+    order: Option<Order>,
+) -> StdResult<Vec<Addr>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let order = order.unwrap_or(Order::Ascending);
+    let (min, max) = page_bounds(start_after, order);
+
+    USER_MAP
+        .keys(deps.storage, min, max, order)
+        .take(limit)
+        .collect()
+}
+
+pub fn query_map(
+    deps: Deps,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+    order: Option<Order>,
+) -> StdResult<Vec<(Addr, String)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let order = order.unwrap_or(Order::Ascending);
+    let (min, max) = page_bounds(start_after, order);
+
+    USER_MAP
+        .range(deps.storage, min, max, order)
+        .take(limit)
+        .collect()
+}
+
+#[entry_point]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> ContractResult<Response> {
+    let report = migration::migrate(deps.storage, CONTRACT_NAME, CONTRACT_VERSION, MIGRATIONS)?;
+    Ok(report.into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msg::ExecuteMsg;
+    use cosmwasm_std::from_json;
+    use cosmwasm_std::testing::{message_info, mock_dependencies, mock_env};
+
+    #[test]
+    fn test_paginated_queries() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let owner = deps.api.addr_make("owner");
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            message_info(&owner, &[]),
+            InstantiateMsg {},
+        )
+        .unwrap();
+
+        let mut users: Vec<Addr> = (0..5)
+            .map(|i| deps.api.addr_make(&format!("user{i}")))
+            .collect();
+        for user in &users {
+            let msg = ExecuteMsg::Update {
+                value: "{}".to_string(),
+            };
+            execute(deps.as_mut(), env.clone(), message_info(user, &[]), msg).unwrap();
+        }
+        // updating a value does not count the user twice
+        let msg = ExecuteMsg::Update {
+            value: "{\"name\":\"again\"}".to_string(),
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            message_info(&users[0], &[]),
+            msg,
+        )
+        .unwrap();
+        users.sort();
+
+        let count: u64 =
+            from_json(query(deps.as_ref(), env.clone(), QueryMsg::Count {}).unwrap()).unwrap();
+        assert_eq!(count, 5);
+
+        let page = query_users(deps.as_ref(), None, Some(2), None).unwrap();
+        assert_eq!(page, users[..2]);
+        let page = query_users(deps.as_ref(), Some(page[1].clone()), Some(2), None).unwrap();
+        assert_eq!(page, users[2..4]);
+
+        let page = query_map(deps.as_ref(), None, Some(2), Some(Order::Descending)).unwrap();
+        assert_eq!(page[0].0, users[4]);
+        assert_eq!(page[1].0, users[3]);
+        let start_after = Some(page[1].0.clone());
+        let page = query_map(deps.as_ref(), start_after, None, Some(Order::Descending)).unwrap();
+        assert_eq!(page.len(), 3);
+        assert_eq!(page[2].0, users[0]);
+
+        // the deprecated variants keep returning everything
+        #[allow(deprecated)]
+        let all: Vec<Addr> =
+            from_json(query(deps.as_ref(), env, QueryMsg::GetUsers {}).unwrap()).unwrap();
+        assert_eq!(all, users);
     }
 }
@@ -6,6 +6,9 @@ pub enum ContractError {
 
     #[error(transparent)]
     JsonError(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Migration(#[from] migration::MigrationError),
 }
 
 #[allow(dead_code)]
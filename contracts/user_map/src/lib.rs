@@ -3,5 +3,10 @@ extern crate core;
 #[cfg(not(feature = "library"))]
 pub mod contract;
 mod error;
+#[cfg(not(feature = "library"))]
+mod migrations;
 pub mod msg;
 mod state;
+
+pub const CONTRACT_NAME: &str = "user-map";
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -0,0 +1,118 @@
+use crate::state::{USER_COUNT, USER_MAP};
+use cosmwasm_std::{Order, StdResult, Storage};
+use migration::Migration;
+
+/// State transforms by the version that introduced them, see
+/// [`migration::migrate`]. Contracts instantiated before versions were
+/// recorded migrate from [`migration::UNVERSIONED`].
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    version: "0.2.0",
+    migrate: count_users,
+}];
+
+/// 0.2.0 keeps a count of the users, seeded here from the existing map.
+///
+/// The whole map is walked in the migration transaction, reading each key
+/// without its value, so the gas it needs grows linearly with the number of
+/// users. A map too large for one transaction's gas limit can't be migrated
+/// to 0.2.0 as is; benchmark the key count against the chain's block gas
+/// limit before upgrading a large deployment.
+fn count_users(storage: &mut dyn Storage) -> StdResult<()> {
+    let count = USER_MAP
+        .keys_raw(storage, None, None, Order::Ascending)
+        .count();
+    USER_COUNT.save(storage, &(count as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::contract::{migrate, query};
+    use crate::msg::{MigrateMsg, QueryMsg};
+    use crate::{CONTRACT_NAME, CONTRACT_VERSION};
+    use cosmwasm_std::testing::{mock_dependencies, mock_env};
+    use cosmwasm_std::{from_json, to_json_vec, Addr, Storage};
+    use cw_storage_plus::Map;
+    use serde_json::Value;
+
+    /// Writes state as stored by an earlier version, in its raw JSON form.
+    fn load_fixture(storage: &mut dyn Storage, fixture: &str) {
+        let fixture: Value = serde_json::from_str(fixture).unwrap();
+        if let Some(contract_info) = fixture.get("contract_info") {
+            storage.set(b"contract_info", &to_json_vec(contract_info).unwrap());
+        }
+        let user_map: Map<&str, Value> = Map::new("user_map");
+        for (user, value) in fixture["user_map"].as_object().unwrap() {
+            user_map.save(storage, user, value).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_migrate_unversioned() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        // state as stored before versions were recorded
+        load_fixture(
+            &mut deps.storage,
+            include_str!("../tests/fixtures/unversioned.json"),
+        );
+
+        let res = migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+        assert_eq!(res.attributes[1].value, migration::UNVERSIONED);
+
+        let version = cw2::get_contract_version(&deps.storage).unwrap();
+        assert_eq!(version.contract, CONTRACT_NAME);
+        assert_eq!(version.version, CONTRACT_VERSION);
+
+        let map: Vec<(Addr, String)> = from_json(
+            query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::Map {
+                    start_after: None,
+                    limit: None,
+                    order: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map[0].0, Addr::unchecked("cosmwasm1alice"));
+        assert_eq!(map[1].1, "{\"name\":\"bob\",\"age\":30}");
+
+        // the count is seeded from the existing entries
+        let count: u64 =
+            from_json(query(deps.as_ref(), env.clone(), QueryMsg::Count {}).unwrap()).unwrap();
+        assert_eq!(count, 2);
+
+        // downgrades are refused
+        cw2::set_contract_version(&mut deps.storage, CONTRACT_NAME, "99.0.0").unwrap();
+        migrate(deps.as_mut(), env, MigrateMsg {}).unwrap_err();
+    }
+
+    #[test]
+    fn test_migrate_v0_1_0() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        load_fixture(
+            &mut deps.storage,
+            include_str!("../tests/fixtures/v0_1_0.json"),
+        );
+
+        let res = migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+        assert_eq!(res.attributes[1].value, "0.1.0");
+        assert_eq!(res.attributes[2].value, CONTRACT_VERSION);
+
+        // 0.1.0 kept no count, so the 0.2.0 step seeds it
+        let count: u64 =
+            from_json(query(deps.as_ref(), env.clone(), QueryMsg::Count {}).unwrap()).unwrap();
+        assert_eq!(count, 3);
+
+        // migrating again leaves the count alone
+        cw2::set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.2.0").unwrap();
+        migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+        let count: u64 = from_json(query(deps.as_ref(), env, QueryMsg::Count {}).unwrap()).unwrap();
+        assert_eq!(count, 3);
+    }
+}
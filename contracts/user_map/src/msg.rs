@@ -1,5 +1,5 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Order};
 
 #[cw_serde]
 pub struct InstantiateMsg {}
@@ -12,12 +12,40 @@ pub enum ExecuteMsg {
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
+    /// Lists every user at once, which fails once the map outgrows the
+    /// query gas limit
+    #[deprecated(note = "use `Users` to page through users")]
     #[returns(Vec<Addr>)]
     GetUsers {},
     #[returns(String)]
     GetValueByUser { address: Addr },
+    /// Lists every entry at once, which fails once the map outgrows the
+    /// query gas limit
+    #[deprecated(note = "use `Map` to page through entries")]
     #[returns(Vec<(Addr, String)>)]
     GetMap {},
+
+    /// Query a page of users, ascending by address unless `order` says
+    /// otherwise
+    #[returns(Vec<Addr>)]
+    Users {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+        order: Option<Order>,
+    },
+
+    /// Query a page of users and their values, ascending by address unless
+    /// `order` says otherwise
+    #[returns(Vec<(Addr, String)>)]
+    Map {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+        order: Option<Order>,
+    },
+
+    /// Query the number of users with a value
+    #[returns(u64)]
+    Count {},
 }
 
 #[cw_serde]
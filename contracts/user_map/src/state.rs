@@ -1,5 +1,9 @@
 use cosmwasm_std::Addr;
-use cw_storage_plus::Map;
+use cw_storage_plus::{Item, Map};
 
 #[allow(dead_code)]
 pub const USER_MAP: Map<Addr, String> = Map::new("user_map");
+
+/// Number of entries in [`USER_MAP`], so counting does not iterate it
+#[allow(dead_code)]
+pub const USER_COUNT: Item<u64> = Item::new("user_count");
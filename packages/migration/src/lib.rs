@@ -0,0 +1,213 @@
+//! Versioned state migrations shared by the contracts in this workspace.
+//!
+//! Each contract declares an ordered list of [`Migration`] steps, one per
+//! version that changed its storage layout. [`migrate`] reads the cw2
+//! version stored by the running code, refuses to cross contracts or
+//! downgrade, runs every step newer than the stored version up to the new
+//! code's version, and finally records the new version.
+
+use cosmwasm_std::{Response, StdError, StdResult, Storage};
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum MigrationError {
+    #[error(transparent)]
+    Std(#[from] StdError),
+
+    #[error("invalid contract version: {version}")]
+    InvalidVersion { version: String },
+
+    #[error("cannot migrate from contract {found} to {expected}")]
+    WrongContract { expected: String, found: String },
+
+    #[error("cannot downgrade from {from} to {to}")]
+    Downgrade { from: String, to: String },
+}
+
+/// A state transform bringing storage written by an earlier version up to
+/// the layout expected by `version`.
+pub struct Migration {
+    pub version: &'static str,
+    pub migrate: fn(&mut dyn Storage) -> StdResult<()>,
+}
+
+/// Outcome of a successful [`migrate`] call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationReport {
+    pub from: String,
+    pub to: String,
+    pub applied: Vec<&'static str>,
+}
+
+impl MigrationReport {
+    /// The response for a `migrate` entry point, recording both versions.
+    pub fn into_response(self) -> Response {
+        Response::new()
+            .add_attribute("method", "migrate")
+            .add_attribute("from_version", self.from)
+            .add_attribute("to_version", self.to)
+    }
+}
+
+/// Version assumed for contracts instantiated before they recorded a cw2
+/// version.
+pub const UNVERSIONED: &str = "0.0.0";
+
+type Version = (u64, u64, u64);
+
+fn parse_version(version: &str) -> Result<Version, MigrationError> {
+    let invalid = || MigrationError::InvalidVersion {
+        version: version.to_string(),
+    };
+    // pre-release and build suffixes do not take part in ordering
+    let core = version.split(['-', '+']).next().unwrap_or_default();
+    let mut parts = core.split('.').map(|part| part.parse::<u64>());
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(Ok(major)), Some(Ok(minor)), Some(Ok(patch)), None) => Ok((major, minor, patch)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Migrates the storage of `contract_name` to `contract_version`, running
+/// every step in `migrations` whose version is newer than the stored one and
+/// not newer than `contract_version`, in ascending version order.
+pub fn migrate(
+    storage: &mut dyn Storage,
+    contract_name: &str,
+    contract_version: &str,
+    migrations: &[Migration],
+) -> Result<MigrationReport, MigrationError> {
+    let stored = cw2::CONTRACT.may_load(storage)?;
+    let from = match stored {
+        Some(stored) if stored.contract != contract_name => {
+            return Err(MigrationError::WrongContract {
+                expected: contract_name.to_string(),
+                found: stored.contract,
+            })
+        }
+        Some(stored) => stored.version,
+        None => UNVERSIONED.to_string(),
+    };
+
+    let from_version = parse_version(&from)?;
+    let to_version = parse_version(contract_version)?;
+    if from_version > to_version {
+        return Err(MigrationError::Downgrade {
+            from,
+            to: contract_version.to_string(),
+        });
+    }
+
+    let mut steps = migrations
+        .iter()
+        .map(|step| Ok((parse_version(step.version)?, step)))
+        .collect::<Result<Vec<_>, MigrationError>>()?;
+    steps.sort_by_key(|(version, _)| *version);
+
+    let mut applied = vec![];
+    for (version, step) in steps {
+        if version > from_version && version <= to_version {
+            (step.migrate)(storage)?;
+            applied.push(step.version);
+        }
+    }
+
+    cw2::set_contract_version(storage, contract_name, contract_version)?;
+
+    Ok(MigrationReport {
+        from,
+        to: contract_version.to_string(),
+        applied,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::MockStorage;
+
+    fn mark_0_2_0(storage: &mut dyn Storage) -> StdResult<()> {
+        storage.set(b"0.2.0", b"1");
+        Ok(())
+    }
+
+    fn mark_0_3_0(storage: &mut dyn Storage) -> StdResult<()> {
+        // steps run in version order, so 0.2.0 must already have run
+        if storage.get(b"0.2.0").is_none() {
+            return Err(StdError::generic_err("0.2.0 has not run"));
+        }
+        storage.set(b"0.3.0", b"1");
+        Ok(())
+    }
+
+    fn mark_0_4_0(storage: &mut dyn Storage) -> StdResult<()> {
+        storage.set(b"0.4.0", b"1");
+        Ok(())
+    }
+
+    const MIGRATIONS: &[Migration] = &[
+        Migration {
+            version: "0.3.0",
+            migrate: mark_0_3_0,
+        },
+        Migration {
+            version: "0.2.0",
+            migrate: mark_0_2_0,
+        },
+        Migration {
+            version: "0.4.0",
+            migrate: mark_0_4_0,
+        },
+    ];
+
+    #[test]
+    fn test_migrate() {
+        let mut storage = MockStorage::new();
+        cw2::set_contract_version(&mut storage, "counter", "0.1.0").unwrap();
+
+        let report = migrate(&mut storage, "counter", "0.3.0", MIGRATIONS).unwrap();
+        assert_eq!(report.from, "0.1.0");
+        assert_eq!(report.to, "0.3.0");
+        assert_eq!(report.applied, vec!["0.2.0", "0.3.0"]);
+        let attributes = report.into_response().attributes;
+        assert_eq!(attributes[1].value, "0.1.0");
+        assert_eq!(attributes[2].value, "0.3.0");
+        assert!(storage.get(b"0.4.0").is_none());
+        assert_eq!(
+            cw2::get_contract_version(&storage).unwrap().version,
+            "0.3.0"
+        );
+
+        // re-running the same version applies nothing
+        let report = migrate(&mut storage, "counter", "0.3.0", MIGRATIONS).unwrap();
+        assert!(report.applied.is_empty());
+
+        let report = migrate(&mut storage, "counter", "0.4.0", MIGRATIONS).unwrap();
+        assert_eq!(report.applied, vec!["0.4.0"]);
+
+        let err = migrate(&mut storage, "counter", "0.3.1", MIGRATIONS).unwrap_err();
+        assert!(matches!(err, MigrationError::Downgrade { .. }));
+
+        let err = migrate(&mut storage, "other", "0.5.0", MIGRATIONS).unwrap_err();
+        assert!(matches!(err, MigrationError::WrongContract { .. }));
+    }
+
+    #[test]
+    fn test_migrate_unversioned() {
+        let mut storage = MockStorage::new();
+        let report = migrate(&mut storage, "counter", "0.2.0", MIGRATIONS).unwrap();
+        assert_eq!(report.from, UNVERSIONED);
+        assert_eq!(report.applied, vec!["0.2.0"]);
+        assert_eq!(
+            cw2::get_contract_version(&storage).unwrap().contract,
+            "counter"
+        );
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("1.2.3").unwrap(), (1, 2, 3));
+        assert_eq!(parse_version("1.10.0-rc.1").unwrap(), (1, 10, 0));
+        assert!(parse_version("1.2").is_err());
+        assert!(parse_version("1.2.x").is_err());
+    }
+}
@@ -0,0 +1,575 @@
+//! An in-memory stand-in for the Xion and Cosmos SDK gRPC services our
+//! contracts depend on, for use with `cw-multi-test`.
+//!
+//! JWT and WebAuthN verification is configured up front on
+//! [`XionMockQuerier`]. WebAuthN responses are modelled by their client data
+//! alone, see [`webauthn_response`]. Authz grants and fee allowances live in the app's
+//! storage: they can be seeded with [`save_grant`] and [`save_allowance`], and
+//! are updated by the `MsgGrant`, `MsgRevoke`, `MsgGrantAllowance` and
+//! `MsgRevokeAllowance` messages contracts send, so a test observes the same
+//! state the chain would.
+
+use cosmos_sdk_proto::cosmos::authz::v1beta1::{
+    GenericAuthorization, Grant, MsgGrant, MsgRevoke, QueryGrantsRequest, QueryGrantsResponse,
+};
+use cosmos_sdk_proto::cosmos::feegrant::v1beta1::{
+    Grant as FeeGrant, MsgGrantAllowance, MsgRevokeAllowance, QueryAllowanceRequest,
+    QueryAllowanceResponse,
+};
+use cosmos_sdk_proto::prost::Message;
+use cosmos_sdk_proto::xion::v1::jwk::{QueryValidateJwtRequest, QueryValidateJwtResponse};
+use cosmos_sdk_proto::xion::v1::{
+    QueryWebAuthNVerifyAuthenticateRequest, QueryWebAuthNVerifyAuthenticateResponse,
+    QueryWebAuthNVerifyRegisterRequest, QueryWebAuthNVerifyRegisterResponse,
+};
+use cosmos_sdk_proto::Any;
+use cosmwasm_std::testing::{MockApi, MockStorage};
+use cosmwasm_std::{
+    Addr, AnyMsg, Api, Binary, BlockInfo, CustomMsg, CustomQuery, Empty, GrpcQuery, Order, Querier,
+    Storage,
+};
+use cw_multi_test::error::{bail, AnyResult};
+use cw_multi_test::{
+    App, AppBuilder, AppResponse, BankKeeper, CosmosRouter, DistributionKeeper, FailingModule,
+    GovFailingModule, IbcFailingModule, StakeKeeper, Stargate, WasmKeeper,
+};
+use cw_storage_plus::Map;
+use serde::de::DeserializeOwned;
+
+/// (granter, grantee, msg_type_url) to the encoded authz `Grant`
+const AUTHZ_GRANTS: Map<(&str, &str, &str), Binary> = Map::new("xion_mock_authz_grants");
+
+/// (granter, grantee) to the encoded fee allowance `Any`
+const FEE_ALLOWANCES: Map<(&str, &str), Binary> = Map::new("xion_mock_fee_allowances");
+
+pub type XionApp = App<
+    BankKeeper,
+    MockApi,
+    MockStorage,
+    FailingModule<Empty, Empty, Empty>,
+    WasmKeeper<Empty, Empty>,
+    StakeKeeper,
+    DistributionKeeper,
+    IbcFailingModule,
+    GovFailingModule,
+    XionMockQuerier,
+>;
+
+#[derive(Clone, Debug, Default)]
+pub struct XionMockQuerier {
+    /// (aud, sub, token) accepted by `ValidateJWT`
+    jwts: Vec<(String, String, String)>,
+    /// (addr, rp, credential) registered and accepted by the WebAuthN queries
+    passkeys: Vec<(String, String, Binary)>,
+}
+
+impl XionMockQuerier {
+    /// Accepts `token` as a valid JWT for `aud` and `sub`. Claims are not
+    /// checked, so the token's payload must still carry whatever the contract
+    /// reads from it.
+    pub fn with_jwt(mut self, aud: &str, sub: &str, token: &str) -> Self {
+        self.jwts
+            .push((aud.to_string(), sub.to_string(), token.to_string()));
+        self
+    }
+
+    /// Registers `credential` as the passkey of `addr` for the relying party
+    /// `rp`. Registration returns it, and authentication with it succeeds,
+    /// for any [`webauthn_response`] to the challenge being verified.
+    pub fn with_passkey(mut self, addr: &Addr, rp: &str, credential: Binary) -> Self {
+        self.passkeys
+            .push((addr.to_string(), rp.to_string(), credential));
+        self
+    }
+
+    /// Builds an app with this querier handling stargate and gRPC requests.
+    pub fn into_app(self) -> XionApp {
+        AppBuilder::new().with_stargate(self).build(|_, _, _| {})
+    }
+
+    fn validate_jwt(&self, request: QueryValidateJwtRequest) -> AnyResult<Binary> {
+        let valid = self.jwts.iter().any(|(aud, sub, token)| {
+            *aud == request.aud && *sub == request.sub && *token == request.sig_bytes
+        });
+        if !valid {
+            bail!(
+                "invalid jwt for aud {} and sub {}",
+                request.aud,
+                request.sub
+            );
+        }
+        Ok(QueryValidateJwtResponse::default().encode_to_vec().into())
+    }
+
+    fn passkey(&self, addr: &str, rp: &str) -> Option<&Binary> {
+        self.passkeys
+            .iter()
+            .find(|(a, r, _)| a == addr && r == rp)
+            .map(|(_, _, credential)| credential)
+    }
+
+    fn webauthn_register(&self, request: QueryWebAuthNVerifyRegisterRequest) -> AnyResult<Binary> {
+        ensure_challenge(&request.data, &request.challenge)?;
+        let Some(credential) = self.passkey(&request.addr, &request.rp) else {
+            bail!(
+                "no passkey registered for {} at {}",
+                request.addr,
+                request.rp
+            );
+        };
+        Ok(QueryWebAuthNVerifyRegisterResponse {
+            credential: credential.to_vec(),
+        }
+        .encode_to_vec()
+        .into())
+    }
+
+    fn webauthn_authenticate(
+        &self,
+        request: QueryWebAuthNVerifyAuthenticateRequest,
+    ) -> AnyResult<Binary> {
+        ensure_challenge(&request.data, &request.challenge)?;
+        match self.passkey(&request.addr, &request.rp) {
+            Some(credential) if credential.as_slice() == request.credential.as_slice() => {
+                Ok(QueryWebAuthNVerifyAuthenticateResponse {}
+                    .encode_to_vec()
+                    .into())
+            }
+            _ => bail!("unknown passkey for {} at {}", request.addr, request.rp),
+        }
+    }
+}
+
+/// The client data of a WebAuthN response to `challenge`, which is all the
+/// mock checks of a response.
+pub fn webauthn_response(challenge: &str) -> Vec<u8> {
+    serde_json::to_vec(&serde_json::json!({
+        "type": "webauthn.get",
+        "challenge": challenge,
+    }))
+    .expect("client data serializes")
+}
+
+fn ensure_challenge(data: &[u8], challenge: &str) -> AnyResult<()> {
+    let client_data: serde_json::Value = serde_json::from_slice(data)?;
+    if client_data["challenge"] != challenge {
+        bail!("webauthn response does not answer challenge {challenge}");
+    }
+    Ok(())
+}
+
+/// The msg type url an authorization is stored under, as in the authz module.
+fn msg_type_url(authorization: &Any) -> AnyResult<String> {
+    Ok(match authorization.type_url.as_str() {
+        "/cosmos.authz.v1beta1.GenericAuthorization" => {
+            GenericAuthorization::decode(authorization.value.as_slice())?.msg
+        }
+        "/cosmos.bank.v1beta1.SendAuthorization" => "/cosmos.bank.v1beta1.MsgSend".to_string(),
+        "/cosmwasm.wasm.v1.ContractExecutionAuthorization" => {
+            "/cosmwasm.wasm.v1.MsgExecuteContract".to_string()
+        }
+        "/cosmwasm.wasm.v1.ContractMigrationAuthorization" => {
+            "/cosmwasm.wasm.v1.MsgMigrateContract".to_string()
+        }
+        type_url => bail!("unsupported authorization {type_url}"),
+    })
+}
+
+fn is_expired(grant: &Grant, block: &BlockInfo) -> bool {
+    grant.expiration.as_ref().is_some_and(|expiration| {
+        (expiration.seconds as u64, expiration.nanos as u32)
+            <= (block.time.seconds(), block.time.subsec_nanos() as u32)
+    })
+}
+
+/// Stores an authz grant, replacing any for the same msg type url.
+pub fn save_grant(
+    storage: &mut dyn Storage,
+    granter: &Addr,
+    grantee: &Addr,
+    grant: Grant,
+) -> AnyResult<()> {
+    let Some(authorization) = &grant.authorization else {
+        bail!("grant without authorization");
+    };
+    let type_url = msg_type_url(authorization)?;
+    AUTHZ_GRANTS.save(
+        storage,
+        (granter.as_str(), grantee.as_str(), &type_url),
+        &grant.encode_to_vec().into(),
+    )?;
+    Ok(())
+}
+
+/// The unexpired authz grants from `granter` to `grantee`, optionally only
+/// the one for `msg_type_url`.
+pub fn load_grants(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    granter: &str,
+    grantee: &str,
+    msg_type_url: Option<&str>,
+) -> AnyResult<Vec<Grant>> {
+    let mut grants = vec![];
+    for item in AUTHZ_GRANTS
+        .prefix((granter, grantee))
+        .range(storage, None, None, Order::Ascending)
+    {
+        let (type_url, grant) = item?;
+        let grant = Grant::decode(grant.as_slice())?;
+        if msg_type_url.is_none_or(|t| t == type_url) && !is_expired(&grant, block) {
+            grants.push(grant);
+        }
+    }
+    Ok(grants)
+}
+
+/// Stores a fee allowance, failing if one already exists as the feegrant
+/// module does.
+pub fn save_allowance(
+    storage: &mut dyn Storage,
+    granter: &Addr,
+    grantee: &Addr,
+    allowance: Any,
+) -> AnyResult<()> {
+    let key = (granter.as_str(), grantee.as_str());
+    if FEE_ALLOWANCES.has(storage, key) {
+        bail!("fee allowance already exists");
+    }
+    FEE_ALLOWANCES.save(storage, key, &allowance.encode_to_vec().into())?;
+    Ok(())
+}
+
+/// The fee allowance from `granter` to `grantee`, if any.
+pub fn load_allowance(
+    storage: &dyn Storage,
+    granter: &str,
+    grantee: &str,
+) -> AnyResult<Option<Any>> {
+    FEE_ALLOWANCES
+        .may_load(storage, (granter, grantee))?
+        .map(|allowance| Any::decode(allowance.as_slice()).map_err(Into::into))
+        .transpose()
+}
+
+fn ensure_granter(sender: &Addr, granter: &str) -> AnyResult<()> {
+    if sender.as_str() != granter {
+        bail!("{sender} cannot act for granter {granter}");
+    }
+    Ok(())
+}
+
+impl Stargate for XionMockQuerier {
+    fn execute_any<ExecC, QueryC>(
+        &self,
+        api: &dyn Api,
+        storage: &mut dyn Storage,
+        _router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        _block: &BlockInfo,
+        sender: Addr,
+        msg: AnyMsg,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: CustomMsg + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        let value = msg.value.as_slice();
+        match msg.type_url.as_str() {
+            "/cosmos.authz.v1beta1.MsgGrant" => {
+                let msg = MsgGrant::decode(value)?;
+                ensure_granter(&sender, &msg.granter)?;
+                let Some(grant) = msg.grant else {
+                    bail!("grant cannot be empty");
+                };
+                let grantee = api.addr_validate(&msg.grantee)?;
+                save_grant(storage, &sender, &grantee, grant)?;
+            }
+            "/cosmos.authz.v1beta1.MsgRevoke" => {
+                let msg = MsgRevoke::decode(value)?;
+                ensure_granter(&sender, &msg.granter)?;
+                let key = (
+                    msg.granter.as_str(),
+                    msg.grantee.as_str(),
+                    msg.msg_type_url.as_str(),
+                );
+                if !AUTHZ_GRANTS.has(storage, key) {
+                    bail!("authorization not found");
+                }
+                AUTHZ_GRANTS.remove(storage, key);
+            }
+            "/cosmos.feegrant.v1beta1.MsgGrantAllowance" => {
+                let msg = MsgGrantAllowance::decode(value)?;
+                ensure_granter(&sender, &msg.granter)?;
+                let Some(allowance) = msg.allowance else {
+                    bail!("allowance cannot be empty");
+                };
+                let grantee = api.addr_validate(&msg.grantee)?;
+                save_allowance(storage, &sender, &grantee, allowance)?;
+            }
+            "/cosmos.feegrant.v1beta1.MsgRevokeAllowance" => {
+                let msg = MsgRevokeAllowance::decode(value)?;
+                ensure_granter(&sender, &msg.granter)?;
+                let key = (msg.granter.as_str(), msg.grantee.as_str());
+                if !FEE_ALLOWANCES.has(storage, key) {
+                    bail!("fee-grant not found");
+                }
+                FEE_ALLOWANCES.remove(storage, key);
+            }
+            type_url => bail!("unexpected any execute: type_url={type_url} from {sender}"),
+        }
+        Ok(AppResponse::default())
+    }
+
+    fn query_grpc(
+        &self,
+        _api: &dyn Api,
+        storage: &dyn Storage,
+        _querier: &dyn Querier,
+        block: &BlockInfo,
+        request: GrpcQuery,
+    ) -> AnyResult<Binary> {
+        let data = request.data.as_slice();
+        match request.path.as_str() {
+            "/xion.jwk.v1.Query/ValidateJWT" => {
+                self.validate_jwt(QueryValidateJwtRequest::decode(data)?)
+            }
+            "/xion.v1.Query/WebAuthNVerifyRegister" => {
+                self.webauthn_register(QueryWebAuthNVerifyRegisterRequest::decode(data)?)
+            }
+            "/xion.v1.Query/WebAuthNVerifyAuthenticate" => {
+                self.webauthn_authenticate(QueryWebAuthNVerifyAuthenticateRequest::decode(data)?)
+            }
+            "/cosmos.authz.v1beta1.Query/Grants" => {
+                let request = QueryGrantsRequest::decode(data)?;
+                let msg_type_url =
+                    Some(request.msg_type_url.as_str()).filter(|type_url| !type_url.is_empty());
+                let grants = load_grants(
+                    storage,
+                    block,
+                    &request.granter,
+                    &request.grantee,
+                    msg_type_url,
+                )?;
+                // like the authz module, a missing grant for a specific msg
+                // type url is an error rather than an empty list
+                if let (Some(type_url), true) = (msg_type_url, grants.is_empty()) {
+                    bail!("authorization not found for {type_url} type");
+                }
+                Ok(QueryGrantsResponse {
+                    grants,
+                    pagination: None,
+                }
+                .encode_to_vec()
+                .into())
+            }
+            "/cosmos.feegrant.v1beta1.Query/Allowance" => {
+                let request = QueryAllowanceRequest::decode(data)?;
+                let Some(allowance) = load_allowance(storage, &request.granter, &request.grantee)?
+                else {
+                    bail!("fee-grant not found");
+                };
+                Ok(QueryAllowanceResponse {
+                    allowance: Some(FeeGrant {
+                        granter: request.granter,
+                        grantee: request.grantee,
+                        allowance: Some(allowance),
+                    }),
+                }
+                .encode_to_vec()
+                .into())
+            }
+            path => bail!("unexpected grpc query: path={path}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::CosmosMsg;
+    use cw_multi_test::Executor;
+
+    #[test]
+    fn test_jwt() {
+        let app = XionMockQuerier::default()
+            .with_jwt("project", "user", "token")
+            .into_app();
+        let validate = |aud: &str, token: &str| {
+            let request = QueryValidateJwtRequest {
+                aud: aud.to_string(),
+                sub: "user".to_string(),
+                sig_bytes: token.to_string(),
+            };
+            app.wrap().query_grpc(
+                "/xion.jwk.v1.Query/ValidateJWT".to_string(),
+                request.encode_to_vec().into(),
+            )
+        };
+
+        validate("project", "token").unwrap();
+        validate("project", "other token").unwrap_err();
+        validate("other project", "token").unwrap_err();
+    }
+
+    #[test]
+    fn test_passkey() {
+        let addr = MockApi::default().addr_make("account");
+        let credential = Binary::from(b"credential");
+        let app = XionMockQuerier::default()
+            .with_passkey(&addr, "https://xion.example", credential.clone())
+            .into_app();
+
+        let register = |challenge: &str, data: Vec<u8>| {
+            let request = QueryWebAuthNVerifyRegisterRequest {
+                addr: addr.to_string(),
+                challenge: challenge.to_string(),
+                rp: "https://xion.example".to_string(),
+                data,
+            };
+            app.wrap().query_grpc(
+                "/xion.v1.Query/WebAuthNVerifyRegister".to_string(),
+                request.encode_to_vec().into(),
+            )
+        };
+        let response = register("register", webauthn_response("register")).unwrap();
+        assert_eq!(
+            QueryWebAuthNVerifyRegisterResponse::decode(response.as_slice())
+                .unwrap()
+                .credential,
+            credential.to_vec()
+        );
+        register("register", webauthn_response("other")).unwrap_err();
+
+        let authenticate = |credential: &Binary, data: Vec<u8>| {
+            let request = QueryWebAuthNVerifyAuthenticateRequest {
+                addr: addr.to_string(),
+                challenge: "tx".to_string(),
+                rp: "https://xion.example".to_string(),
+                credential: credential.to_vec(),
+                data,
+            };
+            app.wrap().query_grpc(
+                "/xion.v1.Query/WebAuthNVerifyAuthenticate".to_string(),
+                request.encode_to_vec().into(),
+            )
+        };
+        authenticate(&credential, webauthn_response("tx")).unwrap();
+        authenticate(&credential, webauthn_response("other tx")).unwrap_err();
+        authenticate(&Binary::from(b"other"), webauthn_response("tx")).unwrap_err();
+    }
+
+    #[test]
+    fn test_authz_and_feegrant() {
+        let mut app = XionMockQuerier::default().into_app();
+        let granter = app.api().addr_make("granter");
+        let grantee = app.api().addr_make("grantee");
+        let any_msg = |type_url: &str, value: Vec<u8>| {
+            CosmosMsg::Any(AnyMsg {
+                type_url: type_url.to_string(),
+                value: value.into(),
+            })
+        };
+
+        let authorization = GenericAuthorization {
+            msg: "/cosmos.bank.v1beta1.MsgSend".to_string(),
+        };
+        let grant = MsgGrant {
+            granter: granter.to_string(),
+            grantee: grantee.to_string(),
+            grant: Some(Grant {
+                authorization: Some(Any {
+                    type_url: "/cosmos.authz.v1beta1.GenericAuthorization".to_string(),
+                    value: authorization.encode_to_vec(),
+                }),
+                expiration: None,
+            }),
+        };
+        // only the granter can grant
+        app.execute(
+            grantee.clone(),
+            any_msg("/cosmos.authz.v1beta1.MsgGrant", grant.encode_to_vec()),
+        )
+        .unwrap_err();
+        app.execute(
+            granter.clone(),
+            any_msg("/cosmos.authz.v1beta1.MsgGrant", grant.encode_to_vec()),
+        )
+        .unwrap();
+
+        let grants = |app: &XionApp, msg_type_url: &str| {
+            let request = QueryGrantsRequest {
+                granter: granter.to_string(),
+                grantee: grantee.to_string(),
+                msg_type_url: msg_type_url.to_string(),
+                pagination: None,
+            };
+            app.wrap()
+                .query_grpc(
+                    "/cosmos.authz.v1beta1.Query/Grants".to_string(),
+                    request.encode_to_vec().into(),
+                )
+                .map(|res| QueryGrantsResponse::decode(res.as_slice()).unwrap().grants)
+        };
+        assert_eq!(grants(&app, "").unwrap().len(), 1);
+        assert_eq!(
+            grants(&app, "/cosmos.bank.v1beta1.MsgSend").unwrap().len(),
+            1
+        );
+        grants(&app, "/cosmos.staking.v1beta1.MsgDelegate").unwrap_err();
+
+        let allowance = Any {
+            type_url: "/cosmos.feegrant.v1beta1.BasicAllowance".to_string(),
+            value: vec![],
+        };
+        let grant_allowance = MsgGrantAllowance {
+            granter: granter.to_string(),
+            grantee: grantee.to_string(),
+            allowance: Some(allowance.clone()),
+        };
+        app.execute(
+            granter.clone(),
+            any_msg(
+                "/cosmos.feegrant.v1beta1.MsgGrantAllowance",
+                grant_allowance.encode_to_vec(),
+            ),
+        )
+        .unwrap();
+        // an existing allowance must be revoked first
+        app.execute(
+            granter.clone(),
+            any_msg(
+                "/cosmos.feegrant.v1beta1.MsgGrantAllowance",
+                grant_allowance.encode_to_vec(),
+            ),
+        )
+        .unwrap_err();
+
+        let query_allowance = |app: &XionApp| {
+            let request = QueryAllowanceRequest {
+                granter: granter.to_string(),
+                grantee: grantee.to_string(),
+            };
+            app.wrap().query_grpc(
+                "/cosmos.feegrant.v1beta1.Query/Allowance".to_string(),
+                request.encode_to_vec().into(),
+            )
+        };
+        let response =
+            QueryAllowanceResponse::decode(query_allowance(&app).unwrap().as_slice()).unwrap();
+        assert_eq!(response.allowance.unwrap().allowance, Some(allowance));
+
+        let revoke = MsgRevokeAllowance {
+            granter: granter.to_string(),
+            grantee: grantee.to_string(),
+        };
+        app.execute(
+            granter.clone(),
+            any_msg(
+                "/cosmos.feegrant.v1beta1.MsgRevokeAllowance",
+                revoke.encode_to_vec(),
+            ),
+        )
+        .unwrap();
+        query_allowance(&app).unwrap_err();
+    }
+}